@@ -1,32 +1,209 @@
 use chrono::NaiveDate;
 use fast_chemail::is_valid_email;
 use isocountry::CountryCode;
+use iso639;
 use phonenumber::PhoneNumber;
+use schema::CURRENT_VERSION;
+use serde::de::{self, Deserialize, Deserializer};
+use serde::{Serialize, Serializer};
 use std::collections::hash_map::DefaultHasher;
+use std::error;
 use std::fmt::{Debug, Display, Error, Formatter};
 use std::hash::{Hash, Hasher};
 use std::slice::Iter;
+use std::str::FromStr;
 use url::Url;
 use url_serde;
 use vfs::VPath;
 
-// Coming up with an address scheme is a pain in itself. Let's at least
-// define some format
+// Raised when the data do not satisfy the invariants of the type being constructed (either
+// directly or via deserialization).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ValidationError {
+    NoContacts,
+    InvalidEmail(String),
+    SpanEndsBeforeStart { from: NaiveDate, to: NaiveDate },
+    ExpiresBeforeObtained { obtained: NaiveDate, expires: NaiveDate },
+    EmptyAddress,
+    InvalidLanguage(String),
+    // The pickers let the user choose days the month does not have, e.g. 31 February.
+    InvalidDate { field: String, year: i32, month: u32, day: u32 },
+    MissingDate(String),
+}
+
+impl ValidationError {
+    // Name of the offending field so that the UI can point the user to it.
+    pub fn field(&self) -> &str {
+        use self::ValidationError::*;
+        match self {
+            NoContacts => "contacts",
+            InvalidEmail(_) => "email",
+            SpanEndsBeforeStart { .. } => "to",
+            ExpiresBeforeObtained { .. } => "expires",
+            EmptyAddress => "address",
+            InvalidLanguage(_) => "language",
+            InvalidDate { ref field, .. } => field,
+            MissingDate(ref field) => field,
+        }
+    }
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        use self::ValidationError::*;
+        write!(f, "{}: ", self.field())?;
+        match self {
+            NoContacts => write!(f, "at least one contact must be provided"),
+            InvalidEmail(ref address) => write!(f, "'{}' is not a valid address", address),
+            SpanEndsBeforeStart { from, to } => {
+                write!(f, "the span cannot end ({}) before it starts ({})", to, from)
+            }
+            ExpiresBeforeObtained { obtained, expires } => write!(
+                f,
+                "the certification cannot expire ({}) before it is obtained ({})",
+                expires, obtained
+            ),
+            EmptyAddress => write!(f, "the address needs at least a street or a city"),
+            InvalidLanguage(ref code) => write!(f, "'{}' is not an ISO 639 code", code),
+            InvalidDate {
+                year, month, day, ..
+            } => write!(f, "{}-{:02}-{:02} is not a valid date", year, month, day),
+            MissingDate(_) => write!(f, "the date has to be picked"),
+        }
+    }
+}
+
+impl error::Error for ValidationError {}
+
+// Coming up with an address scheme is a pain in itself. The free-form lines take whatever
+// precedes the city (the street with the building number, the flat, "c/o ..."), so that the
+// addresses of any country fit in. The order of the rest is left to the formatting.
 // https://en.wikipedia.org/wiki/Address_(geography)
-#[derive(Clone, Debug, Eq, Serialize, Deserialize)]
+#[derive(Clone, Debug, Hash, Eq, PartialEq, Serialize)]
 pub struct Address {
-    pub street: String,
-    pub street_subunit: u32, // this is usually number of the building the address refers to
-    pub postal_code: u32,
+    pub lines: Vec<String>,
+    pub city: String,
+    // The state, the province, the county...
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+    // Kept as written, the postcodes of many countries contain letters (e.g. "SW1A 1AA").
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub postal_code: Option<String>,
     pub country: CountryCode,
 }
 
-impl Hash for Address {
-    fn hash<H: Hasher>(&self, state: &mut H) {
-        self.street.hash(state);
-        self.street_subunit.hash(state);
-        self.postal_code.hash(state);
-        format!("{}", self.country).hash(state);
+// Where the countries put the city, the region and the postcode.
+enum AddressStyle {
+    // "10115 Berlin", the region (if any) on a line of its own.
+    PostcodeCity,
+    // "Springfield, IL 62704".
+    CityRegionPostcode,
+    // The city, the region and the postcode each on a line of its own, e.g. in the UK.
+    PostcodeLast,
+}
+
+impl AddressStyle {
+    fn of(country: CountryCode) -> AddressStyle {
+        match country.alpha2() {
+            "US" | "CA" | "AU" => AddressStyle::CityRegionPostcode,
+            "GB" | "IE" | "RU" | "UA" => AddressStyle::PostcodeLast,
+            _ => AddressStyle::PostcodeCity,
+        }
+    }
+}
+
+impl Address {
+    pub fn new(
+        lines: Vec<String>,
+        city: &str,
+        region: Option<String>,
+        postal_code: Option<String>,
+        country: CountryCode,
+    ) -> Result<Address, ValidationError> {
+        Address {
+            lines,
+            city: city.to_string(),
+            region,
+            postal_code,
+            country,
+        }.validated()
+    }
+
+    fn validated(self) -> Result<Address, ValidationError> {
+        if self.city.trim().is_empty() && self.lines.iter().all(|line| line.trim().is_empty()) {
+            return Err(ValidationError::EmptyAddress);
+        }
+        Ok(self)
+    }
+
+    // The lines of the address as the post of the country expects them, the country last.
+    pub fn formatted_lines(&self) -> Vec<String> {
+        let region = self.region.clone().unwrap_or_default();
+        let postal_code = self.postal_code.clone().unwrap_or_default();
+        let joined = |parts: &[&str], separator| {
+            parts
+                .iter()
+                .filter(|part| !part.is_empty())
+                .cloned()
+                .collect::<Vec<_>>()
+                .join(separator)
+        };
+        let mut lines = self.lines.clone();
+        match AddressStyle::of(self.country) {
+            AddressStyle::PostcodeCity => {
+                lines.push(joined(&[&postal_code, &self.city], " "));
+                lines.push(region);
+            }
+            AddressStyle::CityRegionPostcode => {
+                let region_postcode = joined(&[&region, &postal_code], " ");
+                lines.push(joined(&[&self.city, &region_postcode], ", "));
+            }
+            AddressStyle::PostcodeLast => {
+                lines.extend(vec![self.city.clone(), region, postal_code]);
+            }
+        }
+        lines.push(self.country.name().to_string());
+        lines.retain(|line| !line.trim().is_empty());
+        lines
+    }
+}
+
+// Written on a single line, e.g. in the list of the contacts.
+impl Display for Address {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "{}", self.formatted_lines().join(", "))
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "Address")]
+struct RawAddress {
+    lines: Vec<String>,
+    city: String,
+    #[serde(default)]
+    region: Option<String>,
+    #[serde(default)]
+    postal_code: Option<String>,
+    country: CountryCode,
+}
+
+impl<'de> Deserialize<'de> for Address {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let RawAddress {
+            lines,
+            city,
+            region,
+            postal_code,
+            country,
+        } = RawAddress::deserialize(d)?;
+        Address {
+            lines,
+            city,
+            region,
+            postal_code,
+            country,
+        }.validated()
+        .map_err(de::Error::custom)
     }
 }
 
@@ -41,13 +218,7 @@ where
     first_hasher.finish() == second_hasher.finish()
 }
 
-impl PartialEq for Address {
-    fn eq(&self, other: &Self) -> bool {
-        are_hash_equal(self, other)
-    }
-}
-
-#[derive(Clone, Debug, Hash, Eq, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, Hash, Eq, PartialEq, Serialize)]
 pub struct EmailAddress {
     address: String,
 }
@@ -55,14 +226,33 @@ pub struct EmailAddress {
 // TODO: Note: It would be much more idiomatic to implement the TryFrom/Into trait instead but
 // both traits only belong to nighly-only experimental API. Leave it this way for the time being.
 impl EmailAddress {
-    pub fn from(address: &str) -> Result<EmailAddress, String> {
+    pub fn from(address: &str) -> Result<EmailAddress, ValidationError> {
         if !is_valid_email(address) {
-            return Err("Not a valid address.".to_string());
+            return Err(ValidationError::InvalidEmail(address.to_string()));
         }
         Ok(EmailAddress {
             address: address.to_string(),
         })
     }
+
+    pub fn as_str(&self) -> &str {
+        &self.address
+    }
+}
+
+// The validated types are deserialized into their raw counterparts first, the result is then
+// passed through the same checks as the data coming from the constructors.
+#[derive(Deserialize)]
+#[serde(rename = "EmailAddress")]
+struct RawEmailAddress {
+    address: String,
+}
+
+impl<'de> Deserialize<'de> for EmailAddress {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let raw = RawEmailAddress::deserialize(d)?;
+        EmailAddress::from(&raw.address).map_err(de::Error::custom)
+    }
 }
 
 #[derive(Clone, Eq, Serialize, Deserialize)]
@@ -93,7 +283,7 @@ impl Display for Contact {
         match self {
             Email(ref addr) => write!(f, "E-mail : {}", addr.address),
             Website(ref url) => write!(f, "Website : {}", url),
-            Address(ref addr) => write!(f, "Address : {:?}", addr),
+            Address(ref addr) => write!(f, "Address : {}", addr),
             Phone(ref num) => write!(f, "Phone : {}", num),
         }
     }
@@ -123,7 +313,7 @@ impl PartialEq for Contact {
     }
 }
 
-#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Default, Debug, PartialEq, Serialize)]
 pub struct BasicInfo {
     pub name: String,
     pub surname: String,
@@ -131,8 +321,11 @@ pub struct BasicInfo {
     // have the "raw" NaiveDate, because Default trait implementation is required (and, obviously,
     // there is no such date which could be considered as the default one)
     pub dob: Option<NaiveDate>,
-    // One caveat : we want at least one contact present in the contacts. Tests should catch this.
+    // We want at least one contact present in the contacts, this is checked on construction.
     pub contacts: Vec<Contact>,
+    // Anything else the user wants to tell about themselves, e.g. "Driving licence: B".
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub custom_fields: Vec<CustomField>,
 }
 
 impl<'a> BasicInfo {
@@ -141,37 +334,171 @@ impl<'a> BasicInfo {
         surname: &'a str,
         dob: NaiveDate,
         contacts: Vec<Contact>,
-    ) -> BasicInfo {
-        if contacts.is_empty() {
-            panic!("Contacts cannot be empty. Please provide at least one contact.");
-        }
+    ) -> Result<BasicInfo, ValidationError> {
         BasicInfo {
             name: name.to_string(),
             surname: surname.to_string(),
             dob: Some(dob),
             contacts,
+            custom_fields: vec![],
+        }.validated()
+    }
+
+    fn validated(self) -> Result<BasicInfo, ValidationError> {
+        if self.contacts.is_empty() {
+            return Err(ValidationError::NoContacts);
         }
+        Ok(self)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "BasicInfo")]
+struct RawBasicInfo {
+    name: String,
+    surname: String,
+    dob: Option<NaiveDate>,
+    contacts: Vec<Contact>,
+    #[serde(default)]
+    custom_fields: Vec<CustomField>,
+}
+
+impl<'de> Deserialize<'de> for BasicInfo {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let RawBasicInfo {
+            name,
+            surname,
+            dob,
+            contacts,
+            custom_fields,
+        } = RawBasicInfo::deserialize(d)?;
+        BasicInfo {
+            name,
+            surname,
+            dob,
+            contacts,
+            custom_fields,
+        }.validated()
+        .map_err(de::Error::custom)
     }
 }
 
+// A piece of the personal details the CV has no field for. The fields keep the order the user
+// has entered them in.
 #[derive(Clone, Debug, Hash, Eq, PartialEq, Serialize, Deserialize)]
+pub struct CustomField {
+    pub name: String,
+    pub value: String,
+}
+
+// The end of a time span is stored as a plain date so that the CVs written before open-ended
+// spans were introduced can still be read. Ongoing spans lack the "to" field, a null one is
+// read as ongoing as well.
+mod end_date {
+    use chrono::NaiveDate;
+    use serde::de::{self, IntoDeserializer, Visitor};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::fmt::{self, Formatter};
+
+    pub fn serialize<S: Serializer>(date: &Option<NaiveDate>, s: S) -> Result<S::Ok, S::Error> {
+        match date {
+            Some(date) => date.serialize(s),
+            None => s.serialize_none(),
+        }
+    }
+
+    // Reads the end as Option<NaiveDate> does. The date may also come without the Some around
+    // it, as it is serialized, which matters to the formats telling the two apart.
+    struct EndDateVisitor;
+
+    impl<'de> Visitor<'de> for EndDateVisitor {
+        type Value = Option<NaiveDate>;
+
+        fn expecting(&self, f: &mut Formatter) -> fmt::Result {
+            write!(f, "a date or null")
+        }
+
+        fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+            Ok(None)
+        }
+
+        fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+            Ok(None)
+        }
+
+        fn visit_some<D: Deserializer<'de>>(self, d: D) -> Result<Self::Value, D::Error> {
+            NaiveDate::deserialize(d).map(Some)
+        }
+
+        fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+            NaiveDate::deserialize(v.into_deserializer()).map(Some)
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Option<NaiveDate>, D::Error> {
+        d.deserialize_option(EndDateVisitor)
+    }
+}
+
+#[derive(Clone, Debug, Hash, Eq, PartialEq, Serialize)]
 pub struct TimeSpan {
     from: NaiveDate,
-    to: NaiveDate,
+    // None stands for a span which has not ended yet (a current job, an ongoing degree...).
+    #[serde(default, with = "end_date", skip_serializing_if = "Option::is_none")]
+    to: Option<NaiveDate>,
 }
 
 impl TimeSpan {
-    pub fn new(from: NaiveDate, to: NaiveDate) -> TimeSpan {
-        if from > to {
-            panic!("The lefthand boundary must be less or equal to the righthand one.");
+    pub fn new(from: NaiveDate, to: NaiveDate) -> Result<TimeSpan, ValidationError> {
+        TimeSpan { from, to: Some(to) }.validated()
+    }
+
+    pub fn ongoing(from: NaiveDate) -> TimeSpan {
+        TimeSpan { from, to: None }
+    }
+
+    pub fn from(&self) -> NaiveDate {
+        self.from
+    }
+
+    // None for an ongoing span.
+    pub fn to(&self) -> Option<NaiveDate> {
+        self.to
+    }
+
+    fn validated(self) -> Result<TimeSpan, ValidationError> {
+        match self.to {
+            Some(to) if self.from > to => Err(ValidationError::SpanEndsBeforeStart {
+                from: self.from,
+                to,
+            }),
+            _ => Ok(self),
         }
-        TimeSpan { from, to }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "TimeSpan")]
+struct RawTimeSpan {
+    from: NaiveDate,
+    #[serde(default, with = "end_date")]
+    to: Option<NaiveDate>,
+}
+
+impl<'de> Deserialize<'de> for TimeSpan {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let RawTimeSpan { from, to } = RawTimeSpan::deserialize(d)?;
+        TimeSpan { from, to }.validated().map_err(de::Error::custom)
     }
 }
 
 impl ToString for TimeSpan {
     fn to_string(&self) -> String {
-        self.from.to_string() + "   -   " + &self.to.to_string()
+        let to = match self.to {
+            Some(to) => to.to_string(),
+            None => "present".to_string(),
+        };
+        self.from.to_string() + "   -   " + &to
     }
 }
 
@@ -191,6 +518,131 @@ pub struct Experience {
     pub description: String,
 }
 
+// An open-source (or any other) project the person has taken part in.
+#[derive(Clone, Debug, Hash, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Project {
+    pub span: TimeSpan,
+    pub name: String,
+    #[serde(default, with = "url_serde")]
+    pub url: Option<Url>,
+    // The part the person has played, e.g. "maintainer".
+    pub role: String,
+    #[serde(default)]
+    pub technologies: Vec<String>,
+}
+
+#[derive(Clone, Debug, Hash, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Publication {
+    pub title: String,
+    // The journal, the conference or the publisher.
+    pub venue: String,
+    pub date: NaiveDate,
+    // The works without a DOI may be referred to by an URL instead.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub doi: Option<String>,
+    #[serde(default, with = "url_serde")]
+    pub url: Option<Url>,
+    #[serde(default)]
+    pub co_authors: Vec<String>,
+}
+
+#[derive(Clone, Debug, Hash, Eq, PartialEq, Serialize)]
+pub struct Certification {
+    pub name: String,
+    pub issuer: String,
+    pub obtained: NaiveDate,
+    // None for the certifications which do not expire.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires: Option<NaiveDate>,
+    // The id the issuer verifies the certification by.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub credential_id: Option<String>,
+}
+
+impl Certification {
+    pub fn new(
+        name: &str,
+        issuer: &str,
+        obtained: NaiveDate,
+        expires: Option<NaiveDate>,
+        credential_id: Option<String>,
+    ) -> Result<Certification, ValidationError> {
+        Certification {
+            name: name.to_string(),
+            issuer: issuer.to_string(),
+            obtained,
+            expires,
+            credential_id,
+        }.validated()
+    }
+
+    fn validated(self) -> Result<Certification, ValidationError> {
+        match self.expires {
+            Some(expires) if self.obtained > expires => {
+                Err(ValidationError::ExpiresBeforeObtained {
+                    obtained: self.obtained,
+                    expires,
+                })
+            }
+            _ => Ok(self),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "Certification")]
+struct RawCertification {
+    name: String,
+    issuer: String,
+    obtained: NaiveDate,
+    #[serde(default)]
+    expires: Option<NaiveDate>,
+    #[serde(default)]
+    credential_id: Option<String>,
+}
+
+impl<'de> Deserialize<'de> for Certification {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let RawCertification {
+            name,
+            issuer,
+            obtained,
+            expires,
+            credential_id,
+        } = RawCertification::deserialize(d)?;
+        Certification {
+            name,
+            issuer,
+            obtained,
+            expires,
+            credential_id,
+        }.validated()
+        .map_err(de::Error::custom)
+    }
+}
+
+// A section the user has named, for whatever the built-in sections do not cover (awards,
+// volunteering, talks...).
+#[derive(Clone, Debug, Hash, Eq, PartialEq, Serialize, Deserialize)]
+pub struct CustomSection {
+    pub title: String,
+    #[serde(default)]
+    pub entries: Vec<CustomEntry>,
+}
+
+// The entries of the custom sections are laid out as the built-in ones: the heading with the
+// span, the subheading below it and the body. All but the heading may be left out.
+#[derive(Clone, Debug, Hash, Eq, PartialEq, Serialize, Deserialize)]
+pub struct CustomEntry {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub span: Option<TimeSpan>,
+    pub heading: String,
+    #[serde(default)]
+    pub subheading: String,
+    #[serde(default)]
+    pub body: String,
+}
+
 // Based on the CEFR model.
 #[derive(Clone, Debug, Hash, Eq, PartialEq, Serialize, Deserialize)]
 pub enum LanguageProficiency {
@@ -215,6 +667,19 @@ impl LanguageProficiency {
         PROFS.into_iter()
     }
 
+    // The CEFR name of the level, e.g. "B2".
+    pub fn level(&self) -> &'static str {
+        use self::LanguageProficiency::*;
+        match self {
+            A1 => "A1",
+            A2 => "A2",
+            B1 => "B1",
+            B2 => "B2",
+            C1 => "C1",
+            C2 => "C2",
+        }
+    }
+
     fn description(&self) -> &'static str {
         use self::LanguageProficiency::*;
         match self {
@@ -228,39 +693,163 @@ impl LanguageProficiency {
     }
 }
 
-#[derive(PartialEq, Eq, Hash, Debug, Clone, Serialize, Deserialize)]
-pub enum Language {
-    Czech,
-    Slovak,
-    English,
-    Russian,
-    German,
-    Spanish,
-    Chinese,
-    Dutch,
-    French,
-    Polish,
-    Italian,
-    Arabic,
-    Portugese,
-    Korean,
-    Other,
+// Any language of ISO 639, identified by the ISO 639-3 code. The languages having an ISO 639-1
+// code are known by their endonym as well.
+// https://iso639-3.sil.org/code_tables/639/data
+#[derive(Clone, Debug, Hash, Eq, PartialEq, PartialOrd, Ord)]
+pub struct Language {
+    code: String,
 }
 
 impl Language {
-    pub fn iterator() -> Iter<'static, Self> {
-        use self::Language::*;
-        static LANGS: [Language; 15] = [
-            Czech, Slovak, English, Russian, German, Spanish, Chinese, Dutch, French, Polish,
-            Italian, Arabic, Portugese, Korean, Other,
-        ];
-        LANGS.into_iter()
+    // Accepts both the ISO 639-1 and the ISO 639-3 codes, in any case.
+    pub fn from_code(code: &str) -> Result<Language, ValidationError> {
+        let lowercase = code.trim().to_lowercase();
+        let known = match lowercase.len() {
+            2 => iso639::for_alpha2(&lowercase),
+            _ => iso639::for_alpha3(&lowercase),
+        };
+        match known {
+            Some(language) => Ok(Language {
+                code: language.alpha3.to_string(),
+            }),
+            None => Err(ValidationError::InvalidLanguage(code.to_string())),
+        }
+    }
+
+    // The languages whose English name, endonym or code contain the query, regardless of the
+    // case. Those starting with the query come first.
+    pub fn search(query: &str) -> Vec<Language> {
+        let query = query.trim().to_lowercase();
+        let mut found = iso639::all()
+            .filter_map(|language| {
+                let texts = [
+                    Some(language.name),
+                    language.endonym,
+                    language.alpha2,
+                    Some(language.alpha3),
+                ];
+                let texts = texts.iter().flat_map(|text| *text).map(str::to_lowercase);
+                let positions = texts.filter_map(|text| text.find(&query));
+                positions.min().map(|position| (position > 0, language.alpha3))
+            }).collect::<Vec<_>>();
+        found.sort_by_key(|&(later, _)| later);
+        found
+            .into_iter()
+            .map(|(_, code)| Language {
+                code: code.to_string(),
+            }).collect()
+    }
+
+    // The ISO 639-3 code.
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+
+    // The English name.
+    pub fn name(&self) -> &str {
+        iso639::for_alpha3(&self.code).map_or(&self.code, |language| language.name)
+    }
+
+    pub fn endonym(&self) -> Option<&'static str> {
+        iso639::for_alpha3(&self.code).and_then(|language| language.endonym)
     }
 }
 
 impl Display for Language {
     fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
-        write!(f, "{:?}", self)
+        write!(f, "{}", self.name())
+    }
+}
+
+impl Serialize for Language {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(&self.code)
+    }
+}
+
+impl<'de> Deserialize<'de> for Language {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let code = String::deserialize(d)?;
+        Language::from_code(&code).map_err(de::Error::custom)
+    }
+}
+
+// The self-assessment of the single CEFR skills, as in the Europass CV.
+// https://europass.europa.eu/en/common-european-framework-reference-language-skills
+#[derive(Clone, Debug, Hash, Eq, PartialEq, Serialize, Deserialize)]
+pub struct LanguageSkills {
+    pub listening: LanguageProficiency,
+    pub reading: LanguageProficiency,
+    pub spoken_interaction: LanguageProficiency,
+    pub spoken_production: LanguageProficiency,
+    pub writing: LanguageProficiency,
+}
+
+impl LanguageSkills {
+    // The skills in the order of the Europass grid, see levels.
+    pub const NAMES: [&'static str; 5] = [
+        "Listening",
+        "Reading",
+        "Spoken interaction",
+        "Spoken production",
+        "Writing",
+    ];
+
+    // The same level in every skill.
+    pub fn uniform(proficiency: &LanguageProficiency) -> LanguageSkills {
+        LanguageSkills {
+            listening: proficiency.clone(),
+            reading: proficiency.clone(),
+            spoken_interaction: proficiency.clone(),
+            spoken_production: proficiency.clone(),
+            writing: proficiency.clone(),
+        }
+    }
+
+    // The levels in the order of the Europass grid.
+    pub fn levels(&self) -> [&LanguageProficiency; 5] {
+        [
+            &self.listening,
+            &self.reading,
+            &self.spoken_interaction,
+            &self.spoken_production,
+            &self.writing,
+        ]
+    }
+
+    // The inverse of levels, None unless there is exactly one level for every skill.
+    pub fn from_levels(levels: &[LanguageProficiency]) -> Option<LanguageSkills> {
+        match levels {
+            [listening, reading, spoken_interaction, spoken_production, writing] => {
+                Some(LanguageSkills {
+                    listening: listening.clone(),
+                    reading: reading.clone(),
+                    spoken_interaction: spoken_interaction.clone(),
+                    spoken_production: spoken_production.clone(),
+                    writing: writing.clone(),
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+// E.g. IELTS with the band score 7.5 or Goethe-Zertifikat C1 without any score.
+#[derive(Clone, Debug, Hash, Eq, PartialEq, Serialize, Deserialize)]
+pub struct LanguageCertificate {
+    pub name: String,
+    #[serde(default)]
+    pub score: String,
+}
+
+impl Display for LanguageCertificate {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        if self.score.is_empty() {
+            write!(f, "{}", self.name)
+        } else {
+            write!(f, "{} ({})", self.name, self.score)
+        }
     }
 }
 
@@ -268,14 +857,168 @@ impl Display for Language {
 #[derive(Clone, Debug, Hash, Eq, PartialEq, Serialize, Deserialize)]
 pub struct Lang {
     pub language: Language,
+    // The overall level, the skills may refine it.
     pub proficiency: LanguageProficiency,
     pub notes: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skills: Option<LanguageSkills>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub certificates: Vec<LanguageCertificate>,
+}
+
+impl Lang {
+    // The levels of the single skills, the overall level stands for all of them unless they
+    // have been assessed.
+    pub fn skill_levels(&self) -> LanguageSkills {
+        self.skills
+            .clone()
+            .unwrap_or_else(|| LanguageSkills::uniform(&self.proficiency))
+    }
+}
+
+// Named paper sizes and a custom one, all dimensions are in millimetres.
+// Sources : https://www.papersizes.org/a-paper-sizes.htm
+//           https://www.papersizes.org/us-paper-sizes.htm
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum PaperSize {
+    #[default]
+    A4,
+    A5,
+    Letter,
+    Legal,
+    Custom { width: f64, height: f64 },
+}
+
+impl PaperSize {
+    // Iterates over the named sizes only.
+    pub fn iterator() -> Iter<'static, Self> {
+        use self::PaperSize::*;
+        static SIZES: [PaperSize; 4] = [A4, A5, Letter, Legal];
+        SIZES.iter()
+    }
+
+    // Width and height of the paper in millimetres (portrait orientation).
+    pub fn dimensions(&self) -> (f64, f64) {
+        use self::PaperSize::*;
+        match self {
+            A4 => (210.0, 297.0),
+            A5 => (148.0, 210.0),
+            Letter => (215.9, 279.4),
+            Legal => (215.9, 355.6),
+            Custom { width, height } => (*width, *height),
+        }
+    }
+}
+
+impl Display for PaperSize {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        use self::PaperSize::*;
+        match self {
+            A4 => write!(f, "A4"),
+            A5 => write!(f, "A5"),
+            Letter => write!(f, "US Letter"),
+            Legal => write!(f, "US Legal"),
+            Custom { width, height } => write!(f, "Custom ({} x {} mm)", width, height),
+        }
+    }
+}
+
+// Blank space around the content of every page, in millimetres.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Margins {
+    pub top: f64,
+    pub bottom: f64,
+    pub left: f64,
+    pub right: f64,
+}
+
+impl Default for Margins {
+    fn default() -> Margins {
+        Margins {
+            top: 15.0,
+            bottom: 15.0,
+            left: 15.0,
+            right: 15.0,
+        }
+    }
+}
+
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PageSetup {
+    pub paper: PaperSize,
+    pub margins: Margins,
+}
+
+// Layout of the rendered PDF.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum Template {
+    // Single column, entries indented by a quarter of the page.
+    #[default]
+    Classic,
+    // Contacts and languages in a sidebar next to the main column.
+    TwoColumn,
+    // Smaller type and no indentation.
+    Compact,
+}
+
+impl Template {
+    pub fn iterator() -> Iter<'static, Self> {
+        use self::Template::*;
+        static TEMPLATES: [Template; 3] = [Classic, TwoColumn, Compact];
+        TEMPLATES.iter()
+    }
+}
+
+impl Display for Template {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        use self::Template::*;
+        match self {
+            Classic => write!(f, "Classic"),
+            TwoColumn => write!(f, "Two columns"),
+            Compact => write!(f, "Compact"),
+        }
+    }
+}
+
+// How the languages are rendered, whatever the template.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum LanguageLayout {
+    // A line with the overall level per language.
+    #[default]
+    Summary,
+    // The Europass self-assessment grid with the level of every skill.
+    Grid,
+}
+
+impl LanguageLayout {
+    pub fn iterator() -> Iter<'static, Self> {
+        use self::LanguageLayout::*;
+        static LAYOUTS: [LanguageLayout; 2] = [Summary, Grid];
+        LAYOUTS.iter()
+    }
+}
+
+impl Display for LanguageLayout {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        use self::LanguageLayout::*;
+        match self {
+            Summary => write!(f, "One line per language"),
+            Grid => write!(f, "Self-assessment grid"),
+        }
+    }
 }
 
-#[derive(Default, Builder, Debug, Serialize, Deserialize, PartialEq)]
+#[derive(Clone, Default, Builder, Debug, Serialize, Deserialize, PartialEq)]
 pub struct CV {
+    // The version of the format the CV has been written in, see schema.
+    #[builder(default = "CURRENT_VERSION")]
+    pub version: u32,
     #[builder(default = "None")]
     pub path: Option<String>,
+    // Tells apart the CVs of the same person, e.g. "Backend positions" and "Teaching".
+    #[builder(default = "None")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
     pub basic: BasicInfo,
     #[builder(default = "vec![]")]
     pub education: Vec<Education>,
@@ -283,12 +1026,76 @@ pub struct CV {
     pub experience: Vec<Experience>,
     #[builder(default = "vec![]")]
     pub languages: Vec<Lang>,
+    #[builder(default = "vec![]")]
+    #[serde(default)]
+    pub projects: Vec<Project>,
+    #[builder(default = "vec![]")]
+    #[serde(default)]
+    pub publications: Vec<Publication>,
+    #[builder(default = "vec![]")]
+    #[serde(default)]
+    pub certifications: Vec<Certification>,
+    #[builder(default = "vec![]")]
+    #[serde(default)]
+    pub custom_sections: Vec<CustomSection>,
+    // CVs stored before the page setup was introduced get the default one.
+    #[builder(default = "PageSetup::default()")]
+    #[serde(default)]
+    pub page: PageSetup,
+    #[builder(default = "Template::default()")]
+    #[serde(default)]
+    pub template: Template,
+    #[builder(default = "LanguageLayout::default()")]
+    #[serde(default)]
+    pub language_layout: LanguageLayout,
+    // Set when the CV takes (some of) its data from the profile.
+    #[builder(default = "None")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<ProfileLink>,
 }
 
 impl CV {
-    pub fn set_path(&mut self, path: &VPath) {
+    pub fn set_path(&mut self, path: &dyn VPath) {
         self.path = Some(path.to_string().to_string());
     }
+
+    // Copies the parts the CV keeps in sync from the profile, the other parts are the CV's own
+    // (they override the profile). Returns the parts which have changed.
+    pub fn sync_with(&mut self, profile: &Profile) -> Vec<ProfilePart> {
+        let synced = match self.profile {
+            Some(ref link) => link.synced.clone(),
+            None => return vec![],
+        };
+        synced
+            .into_iter()
+            .filter(|part| self.sync_part(*part, profile))
+            .collect()
+    }
+
+    fn sync_part(&mut self, part: ProfilePart, profile: &Profile) -> bool {
+        use self::ProfilePart::*;
+        let basic = &profile.basic;
+        match part {
+            Name => {
+                replace(&mut self.basic.name, &basic.name)
+                    | replace(&mut self.basic.surname, &basic.surname)
+            }
+            DateOfBirth => replace(&mut self.basic.dob, &basic.dob),
+            Contacts => replace(&mut self.basic.contacts, &basic.contacts),
+            Education => replace(&mut self.education, &profile.education),
+            Experience => replace(&mut self.experience, &profile.experience),
+            Languages => replace(&mut self.languages, &profile.languages),
+        }
+    }
+}
+
+// Returns whether the target had to be changed.
+fn replace<T: Clone + PartialEq>(target: &mut T, source: &T) -> bool {
+    if target == source {
+        return false;
+    }
+    *target = source.clone();
+    true
 }
 
 impl CVBuilder {
@@ -300,52 +1107,186 @@ impl CVBuilder {
     }
 }
 
-#[cfg(test)]
-pub mod test {
-    use super::*;
-    use chrono::Datelike;
-    use serde_test::{assert_ser_tokens, assert_tokens, Token};
+// The data shared by all the CVs of the user. The CVs linked to the profile keep a copy of the
+// shared parts which is refreshed whenever the profile changes (see CV::sync_with), so that
+// every CV can still be read and rendered on its own.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Profile {
+    // The profile is upgraded along with the CVs, see schema.
+    pub version: u32,
+    pub basic: BasicInfo,
+    #[serde(default)]
+    pub education: Vec<Education>,
+    #[serde(default)]
+    pub experience: Vec<Experience>,
+    #[serde(default)]
+    pub languages: Vec<Lang>,
+}
 
-    pub fn basic_cv_factory() -> CV {
-        CVBuilder::default(basic_info_factory()).build().unwrap()
+impl Profile {
+    pub fn from_cv(cv: &CV) -> Profile {
+        Profile {
+            version: CURRENT_VERSION,
+            basic: cv.basic.clone(),
+            education: cv.education.clone(),
+            experience: cv.experience.clone(),
+            languages: cv.languages.clone(),
+        }
     }
+}
 
-    fn basic_info_factory() -> BasicInfo {
-        let email = Contact::Email(EmailAddress::from("peter@raskolnikov.ru").unwrap());
+// Parts of the CV which can be taken from the profile.
+#[derive(Clone, Copy, Debug, Hash, Eq, PartialEq, Serialize, Deserialize)]
+pub enum ProfilePart {
+    // Both the name and the surname.
+    Name,
+    DateOfBirth,
+    Contacts,
+    Education,
+    Experience,
+    Languages,
+}
+
+impl ProfilePart {
+    pub fn iterator() -> Iter<'static, Self> {
+        use self::ProfilePart::*;
+        static PARTS: [ProfilePart; 6] =
+            [Name, DateOfBirth, Contacts, Education, Experience, Languages];
+        PARTS.iter()
+    }
+}
+
+impl Display for ProfilePart {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        use self::ProfilePart::*;
+        match self {
+            Name => write!(f, "name"),
+            DateOfBirth => write!(f, "dob"),
+            Contacts => write!(f, "contacts"),
+            Education => write!(f, "education"),
+            Experience => write!(f, "experience"),
+            Languages => write!(f, "languages"),
+        }
+    }
+}
+
+impl FromStr for ProfilePart {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<ProfilePart, String> {
+        ProfilePart::iterator()
+            .find(|part| part.to_string() == s)
+            .cloned()
+            .ok_or_else(|| format!("'{}' is not a part of the profile", s))
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ProfileLink {
+    // The parts of the CV kept in sync with the profile.
+    pub synced: Vec<ProfilePart>,
+}
+
+// By default, the personal data are shared and the sections are the CV's own.
+impl Default for ProfileLink {
+    fn default() -> ProfileLink {
+        use self::ProfilePart::*;
+        ProfileLink {
+            synced: vec![Name, DateOfBirth, Contacts],
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+    use chrono::Datelike;
+    use schema::cv_from_json;
+    use serde_test::{assert_de_tokens, assert_ser_tokens, assert_tokens, Token};
+    use serde_json;
+
+    pub fn basic_cv_factory() -> CV {
+        CVBuilder::default(basic_info_factory()).build().unwrap()
+    }
+
+    fn basic_info_factory() -> BasicInfo {
+        let email = Contact::Email(EmailAddress::from("peter@raskolnikov.ru").unwrap());
         BasicInfo::new(
             "Peter",
             "Raskolnikov",
             NaiveDate::from_ymd(2000, 1, 1),
             vec![email],
-        )
+        ).unwrap()
     }
 
     #[test]
-    #[should_panic]
     fn timespan_invalid() {
-        TimeSpan::new(
+        let err = TimeSpan::new(
             NaiveDate::from_ymd(2000, 1, 2),
             NaiveDate::from_ymd(2000, 1, 1),
+        ).unwrap_err();
+        assert_eq!(err.field(), "to");
+    }
+
+    #[test]
+    fn deser_invalid_timespan_rejected() {
+        let res = serde_json::from_str::<TimeSpan>(r#"{"from":"2000-01-02","to":"2000-01-01"}"#);
+        assert!(res.unwrap_err().to_string().starts_with("to: "));
+    }
+
+    #[test]
+    fn deser_invalid_email_rejected() {
+        let res = serde_json::from_str::<EmailAddress>(r#"{"address":"not an address"}"#);
+        assert!(res.is_err());
+        assert_eq!(
+            EmailAddress::from("not an address"),
+            Err(ValidationError::InvalidEmail("not an address".to_string()))
         );
     }
 
+    #[test]
+    fn deser_basic_info_without_contacts_rejected() {
+        let res = serde_json::from_str::<BasicInfo>(
+            r#"{"name":"Whata","surname":"Pity","dob":null,"contacts":[]}"#,
+        );
+        assert!(res.unwrap_err().to_string().starts_with("contacts: "));
+    }
+
     #[test]
     fn timespan_ok() {
         let ts = TimeSpan::new(
             NaiveDate::from_ymd(2000, 1, 1),
             NaiveDate::from_ymd(2000, 5, 1),
-        );
-        assert_eq!(ts.from.year(), ts.to.year());
-        assert_eq!(ts.to.month() - ts.from.month(), 4);
+        ).unwrap();
+        let to = ts.to.unwrap();
+        assert_eq!(ts.from.year(), to.year());
+        assert_eq!(to.month() - ts.from.month(), 4);
+    }
+
+    #[test]
+    fn timespan_ongoing_displayed_as_present() {
+        let ts = TimeSpan::ongoing(NaiveDate::from_ymd(2000, 1, 1));
+        assert_eq!(ts.to_string(), "2000-01-01   -   present");
+    }
+
+    #[test]
+    fn timespan_accessors() {
+        let from = NaiveDate::from_ymd(2000, 1, 1);
+        let to = NaiveDate::from_ymd(2001, 1, 1);
+        let ts = TimeSpan::new(from, to).unwrap();
+        assert_eq!((from, Some(to)), (ts.from(), ts.to()));
+        assert_eq!(None, TimeSpan::ongoing(from).to());
     }
 
     // Builders are automatically generated using the derive_builder crate.
     // We should only test that the builder does not build structs which have
     // uninitialized mandatory attributes.
     #[test]
-    #[should_panic]
     fn builder_basic_info_empty_contacts() {
-        BasicInfo::new("Whata", "Pity", NaiveDate::from_ymd(2000, 1, 1), vec![]);
+        assert_eq!(
+            BasicInfo::new("Whata", "Pity", NaiveDate::from_ymd(2000, 1, 1), vec![]),
+            Err(ValidationError::NoContacts)
+        );
     }
 
     #[test]
@@ -364,17 +1305,30 @@ pub mod test {
     fn serde_basic_cv() {
         let cv = basic_cv_factory();
         let mut expected_toks = vec![
-            Token::Struct { name: "CV", len: 5 },
+            Token::Struct { name: "CV", len: 13 },
+            Token::Str("version"),
+            Token::U32(CURRENT_VERSION),
             Token::Str("path"),
             Token::None,
             Token::Str("basic"),
         ];
         expected_toks.append(&mut basic_info_vec());
-        for field in &["education", "experience", "languages"] {
+        let sections = [
+            "education",
+            "experience",
+            "languages",
+            "projects",
+            "publications",
+            "certifications",
+            "custom_sections",
+        ];
+        for field in &sections {
             expected_toks.push(Token::String(field));
             expected_toks.push(Token::Seq { len: Some(0) });
             expected_toks.push(Token::SeqEnd);
         }
+        expected_toks.append(&mut page_setup_toks());
+        expected_toks.append(&mut template_toks());
         expected_toks.push(Token::StructEnd);
         assert_tokens(&cv, &expected_toks);
     }
@@ -434,10 +1388,7 @@ pub mod test {
                 len: 3,
             },
             Token::Str("language"),
-            Token::UnitVariant {
-                name: "Language",
-                variant: "English",
-            },
+            Token::Str("eng"),
             Token::Str("proficiency"),
             Token::UnitVariant {
                 name: "LanguageProficiency",
@@ -454,7 +1405,9 @@ pub mod test {
     #[test]
     fn serde_complex_cv() {
         let mut expected_toks = vec![
-            Token::Struct { name: "CV", len: 5 },
+            Token::Struct { name: "CV", len: 13 },
+            Token::Str("version"),
+            Token::U32(CURRENT_VERSION),
             Token::Str("path"),
             Token::None,
             Token::Str("basic"),
@@ -463,6 +1416,12 @@ pub mod test {
         expected_toks.append(&mut complex_cv_education_toks());
         expected_toks.append(&mut complex_cv_experience_toks());
         expected_toks.append(&mut complex_cv_languages_toks());
+        expected_toks.append(&mut complex_cv_projects_toks());
+        expected_toks.append(&mut complex_cv_publications_toks());
+        expected_toks.append(&mut complex_cv_certifications_toks());
+        expected_toks.append(&mut complex_cv_custom_sections_toks());
+        expected_toks.append(&mut page_setup_toks());
+        expected_toks.append(&mut template_toks());
         expected_toks.push(Token::StructEnd);
         assert_tokens(
             &CVBuilder::default(basic_info_factory())
@@ -471,21 +1430,373 @@ pub mod test {
                     uni_name: "Oxford".to_string(),
                     degree: "MA".to_string(),
                     field_of_study: "Speech recognition".to_string(),
-                }]).experience(vec![Experience {
-                    span: timespan_factory(),
-                    employer: "ABC, corp.".to_string(),
-                    job_name: "accountant".to_string(),
-                    description: "I loved it!".to_string(),
-                }]).languages(vec![Lang {
-                    language: Language::English,
+                }]).experience(vec![experience_factory()])
+                .languages(vec![Lang {
+                    language: Language::from_code("en").unwrap(),
                     proficiency: LanguageProficiency::A1,
                     notes: "".to_string(),
-                }]).build()
+                    skills: None,
+                    certificates: vec![],
+                }]).projects(vec![project_factory()])
+                .publications(vec![publication_factory()])
+                .certifications(vec![certification_factory()])
+                .custom_sections(vec![custom_section_factory()])
+                .build()
                 .unwrap(),
             &expected_toks,
         );
     }
 
+    // An entry with all the parts and one with the heading only.
+    pub fn custom_section_factory() -> CustomSection {
+        CustomSection {
+            title: "Awards".to_string(),
+            entries: vec![
+                CustomEntry {
+                    span: Some(timespan_factory()),
+                    heading: "Employee of the month".to_string(),
+                    subheading: "ABC, corp.".to_string(),
+                    body: "For the tidiest ledgers.".to_string(),
+                },
+                CustomEntry {
+                    span: None,
+                    heading: "Chess club champion".to_string(),
+                    subheading: "".to_string(),
+                    body: "".to_string(),
+                },
+            ],
+        }
+    }
+
+    fn complex_cv_custom_sections_toks() -> Vec<Token> {
+        let mut res = vec![
+            Token::Str("custom_sections"),
+            Token::Seq { len: Some(1) },
+            Token::Struct {
+                name: "CustomSection",
+                len: 2,
+            },
+            Token::Str("title"),
+            Token::Str("Awards"),
+            Token::Str("entries"),
+            Token::Seq { len: Some(2) },
+            Token::Struct {
+                name: "CustomEntry",
+                len: 4,
+            },
+        ];
+        // The span of the entry is optional.
+        let mut span_toks = timespan_factory_toks();
+        span_toks.insert(1, Token::Some);
+        res.append(&mut span_toks);
+        res.append(&mut vec![
+            Token::Str("heading"),
+            Token::Str("Employee of the month"),
+            Token::Str("subheading"),
+            Token::Str("ABC, corp."),
+            Token::Str("body"),
+            Token::Str("For the tidiest ledgers."),
+            Token::StructEnd,
+            Token::Struct {
+                name: "CustomEntry",
+                len: 3,
+            },
+            Token::Str("heading"),
+            Token::Str("Chess club champion"),
+            Token::Str("subheading"),
+            Token::Str(""),
+            Token::Str("body"),
+            Token::Str(""),
+            Token::StructEnd,
+            Token::SeqEnd,
+            Token::StructEnd,
+            Token::SeqEnd,
+        ]);
+        res
+    }
+
+    #[test]
+    fn deser_custom_entry_with_heading_only() {
+        let entry: CustomEntry = serde_json::from_str(r#"{"heading":"Chess"}"#).unwrap();
+        assert_eq!(None, entry.span);
+        assert_eq!(("", ""), (&entry.subheading[..], &entry.body[..]));
+    }
+
+    #[test]
+    fn serde_basic_info_with_custom_fields() {
+        let mut basic_info = basic_info_factory();
+        basic_info.custom_fields = vec![CustomField {
+            name: "Driving licence".to_string(),
+            value: "B".to_string(),
+        }];
+        let mut toks = basic_info_vec();
+        toks[0] = Token::Struct {
+            name: "BasicInfo",
+            len: 5,
+        };
+        toks.pop();
+        toks.append(&mut vec![
+            Token::String("custom_fields"),
+            Token::Seq { len: Some(1) },
+            Token::Struct {
+                name: "CustomField",
+                len: 2,
+            },
+            Token::Str("name"),
+            Token::Str("Driving licence"),
+            Token::Str("value"),
+            Token::Str("B"),
+            Token::StructEnd,
+            Token::SeqEnd,
+            Token::StructEnd,
+        ]);
+        assert_tokens(&basic_info, &toks);
+    }
+
+    pub fn project_factory() -> Project {
+        Project {
+            span: timespan_factory(),
+            name: "cvmaker".to_string(),
+            url: Some(Url::parse("https://example.com/cvmaker").unwrap()),
+            role: "maintainer".to_string(),
+            technologies: vec!["Rust".to_string(), "cursive".to_string()],
+        }
+    }
+
+    fn complex_cv_projects_toks() -> Vec<Token> {
+        let mut res = vec![
+            Token::Str("projects"),
+            Token::Seq { len: Some(1) },
+            Token::Struct {
+                name: "Project",
+                len: 5,
+            },
+        ];
+        res.append(&mut timespan_factory_toks());
+        res.append(&mut vec![
+            Token::Str("name"),
+            Token::Str("cvmaker"),
+            Token::Str("url"),
+            Token::Some,
+            Token::Str("https://example.com/cvmaker"),
+            Token::Str("role"),
+            Token::Str("maintainer"),
+            Token::Str("technologies"),
+            Token::Seq { len: Some(2) },
+            Token::Str("Rust"),
+            Token::Str("cursive"),
+            Token::SeqEnd,
+            Token::StructEnd,
+            Token::SeqEnd,
+        ]);
+        res
+    }
+
+    pub fn publication_factory() -> Publication {
+        Publication {
+            title: "Crime and punishment".to_string(),
+            venue: "The Russian Messenger".to_string(),
+            date: NaiveDate::from_ymd(1866, 1, 1),
+            doi: Some("10.1000/182".to_string()),
+            url: None,
+            co_authors: vec!["Sofia Marmeladova".to_string()],
+        }
+    }
+
+    fn complex_cv_publications_toks() -> Vec<Token> {
+        vec![
+            Token::Str("publications"),
+            Token::Seq { len: Some(1) },
+            Token::Struct {
+                name: "Publication",
+                len: 6,
+            },
+            Token::Str("title"),
+            Token::Str("Crime and punishment"),
+            Token::Str("venue"),
+            Token::Str("The Russian Messenger"),
+            Token::Str("date"),
+            Token::Str("1866-01-01"),
+            Token::Str("doi"),
+            Token::Some,
+            Token::Str("10.1000/182"),
+            Token::Str("url"),
+            Token::None,
+            Token::Str("co_authors"),
+            Token::Seq { len: Some(1) },
+            Token::Str("Sofia Marmeladova"),
+            Token::SeqEnd,
+            Token::StructEnd,
+            Token::SeqEnd,
+        ]
+    }
+
+    pub fn certification_factory() -> Certification {
+        Certification::new(
+            "Certified accountant",
+            "ACCA",
+            NaiveDate::from_ymd(2001, 2, 1),
+            Some(NaiveDate::from_ymd(2004, 2, 1)),
+            Some("ACCA-1234".to_string()),
+        ).unwrap()
+    }
+
+    fn complex_cv_certifications_toks() -> Vec<Token> {
+        vec![
+            Token::Str("certifications"),
+            Token::Seq { len: Some(1) },
+            Token::Struct {
+                name: "Certification",
+                len: 5,
+            },
+            Token::Str("name"),
+            Token::Str("Certified accountant"),
+            Token::Str("issuer"),
+            Token::Str("ACCA"),
+            Token::Str("obtained"),
+            Token::Str("2001-02-01"),
+            Token::Str("expires"),
+            Token::Some,
+            Token::Str("2004-02-01"),
+            Token::Str("credential_id"),
+            Token::Some,
+            Token::Str("ACCA-1234"),
+            Token::StructEnd,
+            Token::SeqEnd,
+        ]
+    }
+
+    #[test]
+    fn serde_certification_without_expiry() {
+        let mut certification = certification_factory();
+        certification.expires = None;
+        certification.credential_id = None;
+        assert_tokens(
+            &certification,
+            &[
+                Token::Struct {
+                    name: "Certification",
+                    len: 3,
+                },
+                Token::Str("name"),
+                Token::Str("Certified accountant"),
+                Token::Str("issuer"),
+                Token::Str("ACCA"),
+                Token::Str("obtained"),
+                Token::Str("2001-02-01"),
+                Token::StructEnd,
+            ],
+        );
+    }
+
+    #[test]
+    fn deser_certification_expiring_before_obtained_rejected() {
+        let res = serde_json::from_str::<Certification>(
+            r#"{"name":"CPA","issuer":"AICPA","obtained":"2001-02-01","expires":"2001-01-01"}"#,
+        );
+        assert!(res.unwrap_err().to_string().starts_with("expires: "));
+    }
+
+    #[test]
+    fn deser_project_and_publication_without_optional_fields() {
+        let project: Project = serde_json::from_str(
+            r#"{"span":{"from":"2000-01-01"},"name":"cvmaker","role":"author"}"#,
+        ).unwrap();
+        assert_eq!((None, vec![]), (project.url, project.technologies));
+        let publication: Publication = serde_json::from_str(
+            r#"{"title":"Notes","venue":"Blog","date":"2000-01-01"}"#,
+        ).unwrap();
+        assert_eq!(None, publication.doi);
+        assert!(publication.co_authors.is_empty());
+    }
+
+    fn page_setup_toks() -> Vec<Token> {
+        vec![
+            Token::Str("page"),
+            Token::Struct {
+                name: "PageSetup",
+                len: 2,
+            },
+            Token::Str("paper"),
+            Token::UnitVariant {
+                name: "PaperSize",
+                variant: "A4",
+            },
+            Token::Str("margins"),
+            Token::Struct {
+                name: "Margins",
+                len: 4,
+            },
+            Token::Str("top"),
+            Token::F64(15.0),
+            Token::Str("bottom"),
+            Token::F64(15.0),
+            Token::Str("left"),
+            Token::F64(15.0),
+            Token::Str("right"),
+            Token::F64(15.0),
+            Token::StructEnd,
+            Token::StructEnd,
+        ]
+    }
+
+    fn template_toks() -> Vec<Token> {
+        vec![
+            Token::Str("template"),
+            Token::UnitVariant {
+                name: "Template",
+                variant: "Classic",
+            },
+            Token::Str("language_layout"),
+            Token::UnitVariant {
+                name: "LanguageLayout",
+                variant: "Summary",
+            },
+        ]
+    }
+
+    #[test]
+    fn serde_custom_paper_size() {
+        assert_tokens(
+            &PaperSize::Custom {
+                width: 100.0,
+                height: 200.0,
+            },
+            &[
+                Token::StructVariant {
+                    name: "PaperSize",
+                    variant: "Custom",
+                    len: 2,
+                },
+                Token::Str("width"),
+                Token::F64(100.0),
+                Token::Str("height"),
+                Token::F64(200.0),
+                Token::StructVariantEnd,
+            ],
+        );
+    }
+
+    #[test]
+    fn deser_cv_without_layout_settings() {
+        let mut toks = vec![
+            Token::Struct { name: "CV", len: 6 },
+            Token::Str("version"),
+            Token::U32(CURRENT_VERSION),
+            Token::Str("path"),
+            Token::None,
+            Token::Str("basic"),
+        ];
+        toks.append(&mut basic_info_vec());
+        for field in &["education", "experience", "languages"] {
+            toks.push(Token::String(field));
+            toks.push(Token::Seq { len: Some(0) });
+            toks.push(Token::SeqEnd);
+        }
+        toks.push(Token::StructEnd);
+        assert_de_tokens(&basic_cv_factory(), &toks);
+    }
+
     #[test]
     fn serde_timespan() {
         assert_tokens(
@@ -504,11 +1815,419 @@ pub mod test {
         );
     }
 
+    #[test]
+    fn serde_ongoing_timespan() {
+        assert_tokens(
+            &TimeSpan::ongoing(NaiveDate::from_ymd(2000, 1, 1)),
+            &[
+                Token::Struct {
+                    name: "TimeSpan",
+                    len: 1,
+                },
+                Token::String("from"),
+                Token::String("2000-01-01"),
+                Token::StructEnd,
+            ],
+        );
+    }
+
+    #[test]
+    fn deser_ongoing_timespan_with_null_end() {
+        assert_de_tokens(
+            &TimeSpan::ongoing(NaiveDate::from_ymd(2000, 1, 1)),
+            &[
+                Token::Struct {
+                    name: "TimeSpan",
+                    len: 2,
+                },
+                Token::Str("from"),
+                Token::Str("2000-01-01"),
+                Token::Str("to"),
+                Token::None,
+                Token::StructEnd,
+            ],
+        );
+        let ongoing: TimeSpan = serde_json::from_str(r#"{"from":"2000-01-01","to":null}"#).unwrap();
+        assert_eq!(ongoing, TimeSpan::ongoing(NaiveDate::from_ymd(2000, 1, 1)));
+    }
+
+    #[test]
+    fn deser_ongoing_timespan_from_json() {
+        let bounded: TimeSpan =
+            serde_json::from_str(r#"{"from":"2000-01-01","to":"2001-01-01"}"#).unwrap();
+        assert_eq!(bounded, timespan_factory());
+        let ongoing: TimeSpan = serde_json::from_str(r#"{"from":"2000-01-01"}"#).unwrap();
+        assert_eq!(ongoing, TimeSpan::ongoing(NaiveDate::from_ymd(2000, 1, 1)));
+        assert_eq!(serde_json::to_string(&ongoing).unwrap(), r#"{"from":"2000-01-01"}"#);
+    }
+
+    // The CVs written by the earlier versions of the application, at least one for every
+    // version of the format. The unversioned ones precede the versioning (see schema).
+    fn fixture(json: &str) -> CV {
+        cv_from_json(json, None).unwrap()
+    }
+
+    fn jane_doe_email() -> Contact {
+        Contact::Email(EmailAddress::from("jane@doe.com").unwrap())
+    }
+
+    #[test]
+    fn fixture_unversioned_baseline() {
+        // The numbered street and the postal code have been upgraded to the second version.
+        let address = Address {
+            lines: vec!["Main 1".to_string()],
+            city: "".to_string(),
+            region: None,
+            postal_code: Some("60200".to_string()),
+            country: CountryCode::for_alpha2("CZ").unwrap(),
+        };
+        let contacts = vec![
+            jane_doe_email(),
+            Contact::Website(Url::parse("https://jane.doe.com/").unwrap()),
+            Contact::Address(address),
+        ];
+        let dob = NaiveDate::from_ymd(1990, 5, 17);
+        let basic = BasicInfo::new("Jane", "Doe", dob, contacts).unwrap();
+        let expected = CVBuilder::default(basic)
+            .path(Some("/tmp/0123456789abcdef0123456789abcdef.json".to_string()))
+            .education(vec![Education {
+                span: TimeSpan::new(
+                    NaiveDate::from_ymd(2010, 9, 1),
+                    NaiveDate::from_ymd(2015, 6, 1),
+                ).unwrap(),
+                uni_name: "Masaryk University".to_string(),
+                degree: "Mgr.".to_string(),
+                field_of_study: "Computer science".to_string(),
+            }]).experience(vec![Experience {
+                span: TimeSpan::new(
+                    NaiveDate::from_ymd(2015, 7, 1),
+                    NaiveDate::from_ymd(2018, 1, 1),
+                ).unwrap(),
+                employer: "ACME".to_string(),
+                job_name: "Developer".to_string(),
+                description: "Backend services".to_string(),
+            }]).languages(vec![Lang {
+                language: Language::from_code("deu").unwrap(),
+                proficiency: LanguageProficiency::B2,
+                notes: "".to_string(),
+                skills: None,
+                certificates: vec![],
+            }]).build()
+            .unwrap();
+        assert_eq!(
+            expected,
+            fixture(include_str!("test/cv_unversioned_baseline.json"))
+        );
+    }
+
+    // Written just before the versioning, with the page setup, the template, the label and the
+    // link to the profile.
+    fn labelled_cv() -> CV {
+        let basic = BasicInfo {
+            name: "Jane".to_string(),
+            surname: "Doe".to_string(),
+            dob: None,
+            contacts: vec![jane_doe_email()],
+            custom_fields: vec![],
+        };
+        CVBuilder::default(basic)
+            .path(Some(
+                "/home/jane/.local/share/cvmaker/0123456789abcdef0123456789abcdef.json"
+                    .to_string(),
+            )).label(Some("Teaching".to_string()))
+            .experience(vec![Experience {
+                span: TimeSpan::ongoing(NaiveDate::from_ymd(2018, 2, 1)),
+                employer: "Masaryk University".to_string(),
+                job_name: "Lecturer".to_string(),
+                description: "Databases".to_string(),
+            }]).page(PageSetup {
+                paper: PaperSize::Custom {
+                    width: 200.0,
+                    height: 250.0,
+                },
+                margins: Margins {
+                    top: 10.0,
+                    bottom: 10.0,
+                    left: 20.0,
+                    right: 20.0,
+                },
+            }).template(Template::TwoColumn)
+            .profile(Some(ProfileLink {
+                synced: vec![
+                    ProfilePart::Name,
+                    ProfilePart::Contacts,
+                    ProfilePart::Experience,
+                ],
+            })).build()
+            .unwrap()
+    }
+
+    #[test]
+    fn fixture_unversioned_labelled() {
+        assert_eq!(
+            labelled_cv(),
+            fixture(include_str!("test/cv_unversioned_labelled.json"))
+        );
+    }
+
+    #[test]
+    fn fixture_version_1() {
+        assert_eq!(labelled_cv(), fixture(include_str!("test/cv_v1.json")));
+    }
+
+    fn downing_street() -> Address {
+        Address::new(
+            vec!["Flat 2".to_string(), "10 Downing Street".to_string()],
+            "London",
+            None,
+            Some("SW1A 2AA".to_string()),
+            CountryCode::for_alpha2("GB").unwrap(),
+        ).unwrap()
+    }
+
+    #[test]
+    fn fixture_version_2() {
+        let mut expected = labelled_cv();
+        expected
+            .basic
+            .contacts
+            .push(Contact::Address(downing_street()));
+        assert_eq!(expected, fixture(include_str!("test/cv_v2.json")));
+    }
+
+    #[test]
+    fn fixture_version_3() {
+        let mut expected = labelled_cv();
+        expected
+            .basic
+            .contacts
+            .push(Contact::Address(downing_street()));
+        expected.languages = vec![
+            Lang {
+                language: Language::from_code("uk").unwrap(),
+                proficiency: LanguageProficiency::C2,
+                notes: "".to_string(),
+                skills: None,
+                certificates: vec![],
+            },
+            Lang {
+                language: Language::from_code("szl").unwrap(),
+                proficiency: LanguageProficiency::A2,
+                notes: "".to_string(),
+                skills: None,
+                certificates: vec![],
+            },
+        ];
+        assert_eq!(expected, fixture(include_str!("test/cv_v3.json")));
+    }
+
+    #[test]
+    fn language_from_either_code() {
+        let ukrainian = Language::from_code(" UK ").unwrap();
+        assert_eq!("ukr", ukrainian.code());
+        assert_eq!("Ukrainian", ukrainian.name());
+        assert_eq!(Some("українська"), ukrainian.endonym());
+        // Silesian only has an ISO 639-3 code.
+        assert_eq!("Silesian", Language::from_code("szl").unwrap().name());
+        assert_eq!(
+            Err(ValidationError::InvalidLanguage("qaa".to_string())),
+            Language::from_code("qaa")
+        );
+        assert_eq!(
+            Err(ValidationError::InvalidLanguage("Elvish".to_string())),
+            Language::from_code("Elvish")
+        );
+    }
+
+    // Assessed in every skill, with a certificate with and one without a score.
+    pub fn assessed_lang_factory() -> Lang {
+        Lang {
+            language: Language::from_code("de").unwrap(),
+            proficiency: LanguageProficiency::B2,
+            notes: "".to_string(),
+            skills: Some(LanguageSkills {
+                spoken_production: LanguageProficiency::B1,
+                writing: LanguageProficiency::B1,
+                ..LanguageSkills::uniform(&LanguageProficiency::B2)
+            }),
+            certificates: vec![
+                LanguageCertificate {
+                    name: "Goethe-Zertifikat B2".to_string(),
+                    score: "".to_string(),
+                },
+                LanguageCertificate {
+                    name: "TestDaF".to_string(),
+                    score: "TDN 4".to_string(),
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn language_skills_rebuilt_from_levels() {
+        let skills = assessed_lang_factory().skills.unwrap();
+        let levels = skills
+            .levels()
+            .iter()
+            .map(|&level| level.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(Some(skills), LanguageSkills::from_levels(&levels));
+        assert_eq!(None, LanguageSkills::from_levels(&levels[1..]));
+    }
+
+    #[test]
+    fn serde_lang_with_skills_and_certificates() {
+        let json = serde_json::to_value(assessed_lang_factory()).unwrap();
+        assert_eq!("B1", json["skills"]["writing"]);
+        assert_eq!("TDN 4", json["certificates"][1]["score"]);
+        let lang: Lang = serde_json::from_value(json).unwrap();
+        assert_eq!(assessed_lang_factory(), lang);
+        let certificate: LanguageCertificate =
+            serde_json::from_str(r#"{"name":"Goethe-Zertifikat B2"}"#).unwrap();
+        assert_eq!("Goethe-Zertifikat B2", certificate.to_string());
+        assert_eq!("TestDaF (TDN 4)", lang.certificates[1].to_string());
+    }
+
+    #[test]
+    fn skill_levels_default_to_overall() {
+        let mut lang = assessed_lang_factory();
+        assert_eq!(&LanguageProficiency::B1, lang.skill_levels().levels()[3]);
+        lang.skills = None;
+        let levels = lang.skill_levels();
+        assert!(levels.levels().iter().all(|&level| *level == LanguageProficiency::B2));
+        lang.certificates = vec![];
+        let json = serde_json::to_value(lang).unwrap();
+        assert!(json.get("skills").is_none());
+        assert!(json.get("certificates").is_none());
+    }
+
+    #[test]
+    fn language_search() {
+        let codes = |query| {
+            Language::search(query)
+                .iter()
+                .map(|language| language.code().to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(vec!["hun"], codes("magyar"));
+        // The names starting with the query come before those only containing it.
+        assert_eq!("slk", codes("slov")[0]);
+        assert!(codes("slov").contains(&"slv".to_string()));
+        assert_eq!(vec!["szl"], codes("SZL"));
+        // The languages having only an ISO 639-3 code are found by the name too.
+        assert!(codes("silesian").contains(&"szl".to_string()));
+        assert!(codes("elvish").is_empty());
+    }
+
+    #[test]
+    fn address_formatted_for_country() {
+        let lines = downing_street().formatted_lines();
+        assert_eq!(
+            vec!["Flat 2", "10 Downing Street", "London", "SW1A 2AA"],
+            &lines[..4]
+        );
+        assert!(lines[4].starts_with("United Kingdom"));
+        let white_house = Address::new(
+            vec!["1600 Pennsylvania Avenue NW".to_string()],
+            "Washington",
+            Some("DC".to_string()),
+            Some("20500".to_string()),
+            CountryCode::for_alpha2("US").unwrap(),
+        ).unwrap();
+        assert_eq!(
+            "1600 Pennsylvania Avenue NW, Washington, DC 20500, United States of America",
+            white_house.to_string()
+        );
+        let castle = Address::new(
+            vec!["Hrad I. nádvoří 1".to_string()],
+            "Praha",
+            None,
+            Some("119 08".to_string()),
+            CountryCode::for_alpha2("CZ").unwrap(),
+        ).unwrap();
+        assert_eq!(
+            "Hrad I. nádvoří 1, 119 08 Praha, Czechia",
+            castle.to_string()
+        );
+    }
+
+    #[test]
+    fn empty_address_rejected() {
+        let country = CountryCode::for_alpha2("CA").unwrap();
+        assert_eq!(
+            Err(ValidationError::EmptyAddress),
+            Address::new(vec![" ".to_string()], "", None, None, country)
+        );
+        let res = serde_json::from_str::<Address>(r#"{"lines":[],"city":"","country":"CA"}"#);
+        assert!(res.unwrap_err().to_string().starts_with("address: "));
+    }
+
+    fn profile_factory() -> Profile {
+        let mut profile = Profile::from_cv(&basic_cv_factory());
+        profile.basic.surname = "Romanovich".to_string();
+        profile.basic.contacts = vec![Contact::Email(
+            EmailAddress::from("rodion@raskolnikov.ru").unwrap(),
+        )];
+        profile.experience = vec![experience_factory()];
+        profile
+    }
+
+    #[test]
+    fn sync_with_profile_reports_changed_parts() {
+        let mut cv = basic_cv_factory();
+        cv.profile = Some(ProfileLink::default());
+        let profile = profile_factory();
+        assert_eq!(
+            vec![ProfilePart::Name, ProfilePart::Contacts],
+            cv.sync_with(&profile)
+        );
+        assert_eq!(profile.basic, cv.basic);
+        // The experience is not synced by default, the CV keeps its own.
+        assert!(cv.experience.is_empty());
+        assert!(cv.sync_with(&profile).is_empty());
+    }
+
+    #[test]
+    fn sync_with_profile_sections_and_overrides() {
+        let mut cv = basic_cv_factory();
+        cv.profile = Some(ProfileLink {
+            synced: vec![ProfilePart::Experience],
+        });
+        let profile = profile_factory();
+        assert_eq!(vec![ProfilePart::Experience], cv.sync_with(&profile));
+        assert_eq!(profile.experience, cv.experience);
+        assert_eq!("Raskolnikov", cv.basic.surname);
+    }
+
+    #[test]
+    fn unlinked_cv_not_synced() {
+        let mut cv = basic_cv_factory();
+        assert!(cv.sync_with(&profile_factory()).is_empty());
+        assert_eq!(basic_cv_factory(), cv);
+    }
+
+    #[test]
+    fn profile_parts_parsed() {
+        for part in ProfilePart::iterator() {
+            assert_eq!(Ok(*part), part.to_string().parse());
+        }
+        assert!("address".parse::<ProfilePart>().is_err());
+    }
+
+    pub fn experience_factory() -> Experience {
+        Experience {
+            span: timespan_factory(),
+            employer: "ABC, corp.".to_string(),
+            job_name: "accountant".to_string(),
+            description: "I loved it!".to_string(),
+        }
+    }
+
     fn timespan_factory() -> TimeSpan {
         TimeSpan::new(
             NaiveDate::from_ymd(2000, 1, 1),
             NaiveDate::from_ymd(2001, 1, 1),
-        )
+        ).unwrap()
     }
 
     fn timespan_factory_toks() -> Vec<Token> {
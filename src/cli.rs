@@ -0,0 +1,523 @@
+// Responsibility: the non-interactive interface of the application, so that the CVs can be
+// managed from scripts. Every command prints its result to the given output, the errors are
+// returned to the caller.
+use base::{Profile, ProfileLink, ProfilePart, CV};
+use chrono::{DateTime, Local};
+use config::{flag_value, DATA_DIR_FLAG};
+use dao::{is_id_file_name, CVDao, CVManager, CVSummary, Revision, SyncedCV};
+use error::{CVError, CVResult};
+use renderer::{default_file_name, render_pdf_to_file};
+use schema::{cv_from_json, profile_from_json};
+use serde::Serialize;
+use serde_json;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+const FROM_JSON_FLAG: &str = "--from-json";
+const HELP_FLAGS: [&str; 2] = ["-h", "--help"];
+const LABEL_FLAG: &str = "--label";
+const PARTS_FLAG: &str = "--parts";
+const OUTPUT_FLAGS: [&str; 2] = ["-o", "--output"];
+// The commands which neither read nor write the stored CVs.
+const STORAGE_FREE_COMMANDS: [&str; 2] = ["help", "validate"];
+// Flags which are followed by a value, the value is not a positional argument.
+const VALUE_FLAGS: [&str; 6] = [
+    DATA_DIR_FLAG,
+    FROM_JSON_FLAG,
+    LABEL_FLAG,
+    PARTS_FLAG,
+    "-o",
+    "--output",
+];
+
+pub const USAGE: &str = "Usage: cvmaker [--data-dir DIR] [COMMAND]
+
+Without a command, the interactive interface is opened.
+
+Commands:
+    list                            list the stored CVs
+    show <id>                       print the CV as JSON
+    new --from-json <file>          store the CV from the file as a new one
+    edit <id> --from-json <file>    replace the stored CV with the one from the file
+    rm <id>                         remove the CV
+    clone <id> [--label <label>]    store a copy of the CV, optionally with a new label
+    render <id> [-o <file>]         render the CV into a PDF file
+    validate <file>                 check that the file contains a valid CV
+    profile show                    print the profile shared by the CVs as JSON
+    profile set --from-json <file>  store the profile from the file
+    profile init <id>               store the profile made from the data of the CV
+    link <id> [--parts <parts>]     keep the parts of the CV in sync with the profile
+    unlink <id>                     stop syncing the CV with the profile
+    history <id>                    list the earlier revisions of the CV
+    diff <id> <rev> [<rev>]         list the fields changed since the revision, up to the
+                                    other revision or the current CV
+    restore <id> <rev>              replace the CV with the revision
+    help, -h, --help                print this message
+
+The CVs are identified by the id printed by the list command.
+The parts are separated by commas, they can be name, dob, contacts, education, experience
+and languages. Only the name, dob and contacts are linked by default. Whenever the profile is
+stored, the linked CVs are updated and listed along with the parts which have changed.
+Whenever a CV changes, its previous content is kept as a revision. The revisions are
+identified by the ids printed by the history command, only the most recent ones are kept.";
+
+// The arguments which are neither flags nor values of the flags.
+fn positional(args: &[String]) -> Vec<&str> {
+    let mut res = vec![];
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if VALUE_FLAGS.contains(&arg.as_str()) {
+            iter.next();
+        } else if !arg.starts_with('-') {
+            res.push(arg.as_str());
+        }
+    }
+    res
+}
+
+// Whether there is a command to be run, as opposed to opening the interactive interface.
+pub fn has_command(args: &[String]) -> bool {
+    !positional(args).is_empty() || wants_help(args)
+}
+
+// Whether the command works with the stored CVs, the storage is left alone otherwise (e.g. the
+// CVs of the older versions are not moved into it).
+pub fn touches_storage(args: &[String]) -> bool {
+    !wants_help(args)
+        && positional(args)
+            .first()
+            .is_some_and(|command| !STORAGE_FREE_COMMANDS.contains(command))
+}
+
+fn wants_help(args: &[String]) -> bool {
+    args.iter().any(|arg| HELP_FLAGS.contains(&arg.as_str()))
+}
+
+fn usage_error(message: &str) -> CVError {
+    CVError::Usage(message.to_string())
+}
+
+// The argument on the given position, the name is used in the error message if it is missing.
+fn argument<'a>(args: &[&'a str], idx: usize, name: &str) -> CVResult<&'a str> {
+    args.get(idx)
+        .cloned()
+        .ok_or_else(|| usage_error(&format!("Missing the {} argument.", name)))
+}
+
+fn required_flag<'a>(args: &'a [String], flag: &str) -> CVResult<&'a str> {
+    flag_value(args, flag).ok_or_else(|| usage_error(&format!("Missing the {} flag.", flag)))
+}
+
+// The ids printed by the list command are the file names of the stored CVs without the
+// extension.
+fn short_id(path: &str) -> &str {
+    Path::new(path)
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or(path)
+}
+
+// Only the ids of the stored CVs are accepted (with or without the extension), so that the
+// commands never touch the files outside of the data directory.
+fn cv_path(data_dir: &str, id: &str) -> CVResult<String> {
+    let file_name = if id.ends_with(".json") {
+        id.to_string()
+    } else {
+        format!("{}.json", id)
+    };
+    if !is_id_file_name(&file_name) {
+        return Err(usage_error(&format!("'{}' is not the id of a stored CV.", id)));
+    }
+    Ok(format!("{}/{}", data_dir, file_name))
+}
+
+fn read_file(path: &str) -> CVResult<String> {
+    fs::read_to_string(path).map_err(|err| CVError::io(path, err))
+}
+
+// Reads the CV from a file outside of the storage, the older formats are upgraded and the data
+// are validated on the way.
+fn read_cv_file(path: &str) -> CVResult<CV> {
+    cv_from_json(&read_file(path)?, Some(path))
+}
+
+fn read_profile_file(path: &str) -> CVResult<Profile> {
+    profile_from_json(&read_file(path)?, Some(path))
+}
+
+fn parse_parts(parts: &str) -> CVResult<Vec<ProfilePart>> {
+    parts
+        .split(',')
+        .map(|part| part.trim().parse().map_err(|err: String| usage_error(&err)))
+        .collect()
+}
+
+fn to_json<S: Serialize>(value: &S) -> CVResult<String> {
+    serde_json::to_string_pretty(value)
+        .map_err(|source| CVError::Serialization { path: None, source })
+}
+
+fn write_out<W: Write>(out: &mut W, text: &str) -> CVResult<()> {
+    writeln!(out, "{}", text).map_err(|err| CVError::io("<stdout>", err))
+}
+
+// The label is printed only when the CV has one.
+fn summary_line(summary: &CVSummary) -> String {
+    let mut line = format!(
+        "{}\t{} {}",
+        short_id(&summary.id),
+        summary.name,
+        summary.surname
+    );
+    if let Some(ref label) = summary.label {
+        line += &format!("\t{}", label);
+    }
+    line
+}
+
+fn synced_line(synced: &SyncedCV) -> String {
+    let parts: Vec<String> = synced.parts.iter().map(|part| part.to_string()).collect();
+    format!("{}\t{}", summary_line(&synced.summary), parts.join(","))
+}
+
+fn revision_line(revision: &Revision) -> String {
+    let replaced: DateTime<Local> = revision.replaced.into();
+    format!("{}\t{}", revision.id, replaced.format("%Y-%m-%d %H:%M:%S"))
+}
+
+fn write_synced<W: Write>(out: &mut W, synced: &[SyncedCV]) -> CVResult<()> {
+    for cv in synced {
+        write_out(out, &synced_line(cv))?;
+    }
+    Ok(())
+}
+
+// Runs the command given by the arguments (without the program name) on the CVs stored in the
+// data directory.
+pub fn run<W: Write>(data_dir: &str, args: &[String], out: &mut W) -> CVResult<()> {
+    if wants_help(args) {
+        return write_out(out, USAGE);
+    }
+    let positional = positional(args);
+    let command = argument(&positional, 0, "command")?;
+    // The data directory is created along with the manager, so the commands which do not need
+    // the storage never create it.
+    let manager = || CVDao::new(data_dir);
+    let read_stored = |manager: &CVDao, idx: usize| -> CVResult<CV> {
+        let id = argument(&positional, idx, "id")?;
+        let path = cv_path(data_dir, id)?;
+        let mut cv = manager.read_cv(&path)?;
+        cv.path = Some(path);
+        Ok(cv)
+    };
+    match command {
+        "list" => {
+            let listing = manager()?.list_cvs()?;
+            for summary in listing.summaries {
+                write_out(out, &summary_line(&summary))?;
+            }
+            // The output lists the readable CVs only, scripts may rely on it.
+            for (_, err) in listing.unreadable {
+                eprintln!("Warning: {}", err);
+            }
+            Ok(())
+        }
+        "show" => write_out(out, &to_json(&read_stored(&manager()?, 1)?)?),
+        "new" => {
+            let mut cv = read_cv_file(required_flag(args, FROM_JSON_FLAG)?)?;
+            cv.path = None;
+            manager()?.add_cv(&mut cv)?;
+            write_out(out, short_id(cv.path.as_ref().unwrap()))
+        }
+        "edit" => {
+            let manager = manager()?;
+            let stored = read_stored(&manager, 1)?;
+            let mut cv = read_cv_file(required_flag(args, FROM_JSON_FLAG)?)?;
+            cv.path = stored.path;
+            manager.update_cv(&cv)
+        }
+        "rm" => {
+            let manager = manager()?;
+            manager.remove_cv(&mut read_stored(&manager, 1)?)
+        }
+        "clone" => {
+            let path = cv_path(data_dir, argument(&positional, 1, "id")?)?;
+            let copy = manager()?.clone_cv(&path, flag_value(args, LABEL_FLAG))?;
+            write_out(out, short_id(copy.path.as_ref().unwrap()))
+        }
+        "render" => {
+            let cv = read_stored(&manager()?, 1)?;
+            let output = OUTPUT_FLAGS
+                .iter()
+                .filter_map(|flag| flag_value(args, flag))
+                .next()
+                .map(str::to_string)
+                .unwrap_or_else(|| default_file_name(&cv));
+            // The warnings do not belong to the output, which scripts may rely on.
+            for warning in render_pdf_to_file(&cv, &output)? {
+                eprintln!("Warning: {}", warning);
+            }
+            write_out(out, &output)
+        }
+        "validate" => {
+            let file = argument(&positional, 1, "file")?;
+            read_cv_file(file)?;
+            write_out(out, &format!("{}: OK", file))
+        }
+        "profile" => match argument(&positional, 1, "profile command")? {
+            "show" => write_out(out, &to_json(&manager()?.read_profile()?)?),
+            "set" => {
+                let profile = read_profile_file(required_flag(args, FROM_JSON_FLAG)?)?;
+                write_synced(out, &manager()?.update_profile(&profile)?)
+            }
+            "init" => {
+                let manager = manager()?;
+                let profile = Profile::from_cv(&read_stored(&manager, 2)?);
+                write_synced(out, &manager.update_profile(&profile)?)
+            }
+            other => Err(usage_error(&format!("Unknown profile command '{}'.", other))),
+        },
+        "link" => {
+            let manager = manager()?;
+            let mut cv = read_stored(&manager, 1)?;
+            let synced = match flag_value(args, PARTS_FLAG) {
+                Some(parts) => parse_parts(parts)?,
+                None => ProfileLink::default().synced,
+            };
+            let profile = manager.read_profile()?;
+            cv.profile = Some(ProfileLink { synced });
+            let parts = cv.sync_with(&profile);
+            manager.update_cv(&cv)?;
+            if parts.is_empty() {
+                return Ok(());
+            }
+            let synced = SyncedCV {
+                summary: CVSummary::new(&cv, None),
+                parts,
+            };
+            write_out(out, &synced_line(&synced))
+        }
+        "unlink" => {
+            let manager = manager()?;
+            let mut cv = read_stored(&manager, 1)?;
+            cv.profile = None;
+            manager.update_cv(&cv)
+        }
+        "history" => {
+            let path = cv_path(data_dir, argument(&positional, 1, "id")?)?;
+            for revision in manager()?.list_revisions(&path)? {
+                write_out(out, &revision_line(&revision))?;
+            }
+            Ok(())
+        }
+        "diff" => {
+            let path = cv_path(data_dir, argument(&positional, 1, "id")?)?;
+            let old = argument(&positional, 2, "revision")?;
+            for change in manager()?.diff_revisions(&path, old, positional.get(3).cloned())? {
+                write_out(out, &change.to_string())?;
+            }
+            Ok(())
+        }
+        "restore" => {
+            let path = cv_path(data_dir, argument(&positional, 1, "id")?)?;
+            manager()?
+                .restore_revision(&path, argument(&positional, 2, "revision")?)
+                .map(|_| ())
+        }
+        "help" => write_out(out, USAGE),
+        other => Err(usage_error(&format!("Unknown command '{}'.", other))),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use base::test::basic_cv_factory;
+    use std::env;
+    use std::str;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    // Runs the command in the data directory, returns what it printed.
+    fn run_cmd(data_dir: &Path, cmd: &[&str]) -> CVResult<String> {
+        let mut out = vec![];
+        run(&data_dir.to_string_lossy(), &args(cmd), &mut out)?;
+        Ok(str::from_utf8(&out).unwrap().to_string())
+    }
+
+    fn test_dir(name: &str) -> ::std::path::PathBuf {
+        let dir = env::temp_dir().join(name);
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn write_cv_file(dir: &Path, cv: &CV) -> String {
+        let file = dir.join("input.json");
+        fs::write(&file, serde_json::to_string(cv).unwrap()).unwrap();
+        file.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn positional_arguments_skip_flags() {
+        let args = args(&["--data-dir", "/dir", "render", "abc", "-o", "out.pdf"]);
+        assert_eq!(vec!["render", "abc"], positional(&args));
+        assert!(!has_command(&args[..2]));
+        assert!(has_command(&args));
+    }
+
+    #[test]
+    fn storage_touched_by_storage_commands_only() {
+        assert!(touches_storage(&args(&["list"])));
+        assert!(touches_storage(&args(&["--data-dir", "/dir", "profile", "show"])));
+        assert!(!touches_storage(&args(&["validate", "cv.json"])));
+        assert!(!touches_storage(&args(&["help"])));
+        assert!(!touches_storage(&args(&["list", "--help"])));
+    }
+
+    #[test]
+    fn new_list_show_and_remove() {
+        let dir = test_dir("cvmaker_cli_new_list_show_and_remove");
+        let file = write_cv_file(&dir, &basic_cv_factory());
+        let id = run_cmd(&dir, &["new", "--from-json", &file]).unwrap();
+        let id = id.trim();
+        assert_eq!(32, id.len());
+        let listed = run_cmd(&dir, &["list"]).unwrap();
+        assert_eq!(format!("{}\tPeter Raskolnikov\n", id), listed);
+        let shown: CV = serde_json::from_str(&run_cmd(&dir, &["show", id]).unwrap()).unwrap();
+        assert_eq!(basic_cv_factory().basic, shown.basic);
+        run_cmd(&dir, &["rm", id]).unwrap();
+        assert_eq!("", run_cmd(&dir, &["list"]).unwrap());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn edit_and_clone() {
+        let dir = test_dir("cvmaker_cli_edit_and_clone");
+        let mut cv = basic_cv_factory();
+        let file = write_cv_file(&dir, &cv);
+        let id = run_cmd(&dir, &["new", "--from-json", &file]).unwrap();
+        let id = id.trim();
+        cv.basic.name = "Rodion".to_string();
+        let file = write_cv_file(&dir, &cv);
+        run_cmd(&dir, &["edit", id, "--from-json", &file]).unwrap();
+        let copy = run_cmd(&dir, &["clone", id, "--label", "Teaching"]).unwrap();
+        assert_ne!(id, copy.trim());
+        let listed = run_cmd(&dir, &["list"]).unwrap();
+        assert_eq!(2, listed.matches("Rodion Raskolnikov").count());
+        assert!(listed.contains(&format!("{}\tRodion Raskolnikov\tTeaching\n", copy.trim())));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn profile_synced_into_linked_cvs() {
+        let dir = test_dir("cvmaker_cli_profile_synced_into_linked_cvs");
+        let mut cv = basic_cv_factory();
+        let file = write_cv_file(&dir, &cv);
+        let linked = run_cmd(&dir, &["new", "--from-json", &file]).unwrap();
+        let other = run_cmd(&dir, &["new", "--from-json", &file]).unwrap();
+        let (linked, other) = (linked.trim(), other.trim());
+        match run_cmd(&dir, &["link", linked]) {
+            Err(CVError::NoProfile) => (),
+            res => panic!("Expected the missing profile to be reported, got {:?}", res),
+        }
+        assert_eq!("", run_cmd(&dir, &["profile", "init", other]).unwrap());
+        run_cmd(&dir, &["link", linked, "--parts", "name,experience"]).unwrap();
+        cv.basic.surname = "Romanovich".to_string();
+        let profile = dir.join("profile_input.json");
+        fs::write(&profile, to_json(&Profile::from_cv(&cv)).unwrap()).unwrap();
+        let report = run_cmd(&dir, &["profile", "set", "--from-json", &profile.to_string_lossy()]);
+        assert_eq!(
+            format!("{}\tPeter Romanovich\tname\n", linked),
+            report.unwrap()
+        );
+        let listed = run_cmd(&dir, &["list"]).unwrap();
+        assert!(listed.contains(&format!("{}\tPeter Raskolnikov", other)));
+        run_cmd(&dir, &["unlink", linked]).unwrap();
+        assert!(run_cmd(&dir, &["link", linked, "--parts", "address"]).is_err());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn history_diff_and_restore() {
+        let dir = test_dir("cvmaker_cli_history_diff_and_restore");
+        let mut cv = basic_cv_factory();
+        let file = write_cv_file(&dir, &cv);
+        let id = run_cmd(&dir, &["new", "--from-json", &file]).unwrap();
+        let id = id.trim();
+        assert_eq!("", run_cmd(&dir, &["history", id]).unwrap());
+        cv.basic.name = "Rodion".to_string();
+        let file = write_cv_file(&dir, &cv);
+        run_cmd(&dir, &["edit", id, "--from-json", &file]).unwrap();
+        let history = run_cmd(&dir, &["history", id]).unwrap();
+        assert_eq!(1, history.lines().count());
+        let revision = history.split('\t').next().unwrap();
+        assert_eq!(
+            "basic.name: 'Peter' -> 'Rodion'\n",
+            run_cmd(&dir, &["diff", id, revision]).unwrap()
+        );
+        run_cmd(&dir, &["restore", id, revision]).unwrap();
+        assert!(run_cmd(&dir, &["list"]).unwrap().contains("Peter Raskolnikov"));
+        assert_eq!(2, run_cmd(&dir, &["history", id]).unwrap().lines().count());
+        match run_cmd(&dir, &["restore", id, "42"]) {
+            Err(CVError::NoRevision(_)) => (),
+            res => panic!("Expected the missing revision to be reported, got {:?}", res),
+        }
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn validate_reports_invalid_cv() {
+        let dir = test_dir("cvmaker_cli_validate_reports_invalid_cv");
+        let file = write_cv_file(&dir, &basic_cv_factory());
+        assert!(run_cmd(&dir, &["validate", &file]).unwrap().ends_with(": OK\n"));
+        let json = fs::read_to_string(&file).unwrap().replace("@", "");
+        fs::write(&file, json).unwrap();
+        match run_cmd(&dir, &["validate", &file]) {
+            Err(CVError::Serialization { .. }) => (),
+            res => panic!("Expected the CV to be rejected, got {:?}", res),
+        }
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn paths_rejected_as_ids() {
+        let dir = test_dir("cvmaker_cli_paths_rejected_as_ids");
+        let file = write_cv_file(&dir, &basic_cv_factory());
+        let outside = format!("{}/../{}", dir.to_string_lossy(), "outside.json");
+        for id in &[file.as_str(), "../input.json", &outside, "input"] {
+            match run_cmd(&dir, &["rm", id]) {
+                Err(CVError::Usage(_)) => (),
+                res => panic!("Expected {} to be rejected, got {:?}", id, res),
+            }
+        }
+        assert!(Path::new(&file).exists());
+        assert!(!dir.join("history").exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn validate_leaves_data_dir_alone() {
+        let dir = test_dir("cvmaker_cli_validate_leaves_data_dir_alone");
+        let file = write_cv_file(&dir, &basic_cv_factory());
+        let data_dir = dir.join("data");
+        assert!(run_cmd(&data_dir, &["validate", &file]).is_ok());
+        assert!(!data_dir.exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn usage_errors() {
+        let dir = test_dir("cvmaker_cli_usage_errors");
+        for cmd in &[&["frobnicate"][..], &["show"], &["new"]] {
+            match run_cmd(&dir, cmd) {
+                Err(CVError::Usage(_)) => (),
+                res => panic!("Expected a usage error for {:?}, got {:?}", cmd, res),
+            }
+        }
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
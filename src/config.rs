@@ -0,0 +1,207 @@
+// Responsibility: decide where the application keeps its data. The location is taken from (in
+// this order) the command line flag, the environment variable, the config file and, when none of
+// them is given, from the XDG base directory specification.
+// https://specifications.freedesktop.org/basedir-spec/basedir-spec-latest.html
+use error::{CVError, CVResult};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use toml;
+
+pub const DATA_DIR_FLAG: &str = "--data-dir";
+pub const DATA_DIR_ENV: &str = "CVMAKER_DATA_DIR";
+// Directory the CVs were stored in before the data directory was introduced.
+pub const LEGACY_DATA_DIR: &str = "/tmp";
+const APP_DIR: &str = "cvmaker";
+const CONFIG_FILE: &str = "config.toml";
+
+// Contents of the config file, every key is optional.
+#[derive(Debug, Default, Deserialize)]
+struct ConfigFile {
+    data_dir: Option<PathBuf>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Config {
+    pub data_dir: PathBuf,
+}
+
+impl Config {
+    // Resolves the configuration from the command line arguments (without the program name)
+    // and the environment of the process.
+    pub fn resolve(args: &[String]) -> CVResult<Config> {
+        Self::resolve_with(args, env_var)
+    }
+
+    // The environment is passed in as a function so that the resolution can be tested without
+    // touching the variables of the process.
+    fn resolve_with<F>(args: &[String], var: F) -> CVResult<Config>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        if let Some(dir) = flag_value(args, DATA_DIR_FLAG) {
+            return Ok(Config {
+                data_dir: PathBuf::from(dir),
+            });
+        }
+        if let Some(dir) = var(DATA_DIR_ENV) {
+            return Ok(Config {
+                data_dir: PathBuf::from(dir),
+            });
+        }
+        let from_file = match xdg_dir(&var, "XDG_CONFIG_HOME", ".config") {
+            Some(config_home) => read_config_file(&config_home.join(APP_DIR).join(CONFIG_FILE))?,
+            None => ConfigFile::default(),
+        };
+        let xdg_data_dir = || xdg_dir(&var, "XDG_DATA_HOME", ".local/share");
+        let data_dir = from_file
+            .data_dir
+            .or_else(|| xdg_data_dir().map(|dir| dir.join(APP_DIR)))
+            .unwrap_or_else(|| PathBuf::from(LEGACY_DATA_DIR));
+        Ok(Config { data_dir })
+    }
+
+    pub fn data_dir_str(&self) -> String {
+        self.data_dir.to_string_lossy().into_owned()
+    }
+}
+
+// Empty variables are treated as unset, as the XDG specification requires.
+fn env_var(name: &str) -> Option<String> {
+    ::std::env::var(name).ok().filter(|value| !value.is_empty())
+}
+
+// Returns the value of the flag given either as "--flag value" or "--flag=value".
+pub fn flag_value<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    let prefix = format!("{}=", flag);
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == flag {
+            return iter.next().map(|value| value.as_str());
+        }
+        if arg.starts_with(&prefix) {
+            return Some(&arg[prefix.len()..]);
+        }
+    }
+    None
+}
+
+// The XDG directory given by the variable, relative paths are ignored as the specification
+// requires. Falls back to the directory under the home of the user.
+fn xdg_dir<F>(var: &F, name: &str, fallback: &str) -> Option<PathBuf>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    var(name)
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| var("HOME").map(|home| Path::new(&home).join(fallback)))
+}
+
+fn read_config_file(path: &Path) -> CVResult<ConfigFile> {
+    let path_str = path.to_string_lossy();
+    match fs::read_to_string(path) {
+        Ok(content) => toml::from_str(&content).map_err(|source| CVError::Config {
+            path: path_str.into_owned(),
+            source,
+        }),
+        Err(ref err) if err.kind() == io::ErrorKind::NotFound => Ok(ConfigFile::default()),
+        Err(err) => Err(CVError::io(&path_str, err)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::HashMap;
+    use std::env;
+
+    fn resolve(args: &[&str], vars: &[(&str, &str)]) -> PathBuf {
+        let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        Config::resolve_with(&args, |name| vars.get(name).cloned())
+            .unwrap()
+            .data_dir
+    }
+
+    // A config directory with the config file setting the data directory to "/from/file".
+    fn config_home(name: &str) -> PathBuf {
+        let config_home = env::temp_dir().join(name);
+        fs::create_dir_all(config_home.join(APP_DIR)).unwrap();
+        fs::write(
+            config_home.join(APP_DIR).join(CONFIG_FILE),
+            "data_dir = \"/from/file\"\n",
+        ).unwrap();
+        config_home
+    }
+
+    #[test]
+    fn xdg_data_home_used_by_default() {
+        let dir = resolve(&[], &[("XDG_DATA_HOME", "/xdg/data"), ("HOME", "/home/peter")]);
+        assert_eq!(PathBuf::from("/xdg/data/cvmaker"), dir);
+    }
+
+    #[test]
+    fn home_used_without_xdg_data_home() {
+        let dir = resolve(&[], &[("XDG_DATA_HOME", "relative"), ("HOME", "/home/peter")]);
+        assert_eq!(PathBuf::from("/home/peter/.local/share/cvmaker"), dir);
+    }
+
+    #[test]
+    fn config_file_overrides_xdg() {
+        let config_home = config_home("cvmaker_config_file_overrides_xdg");
+        let dir = resolve(
+            &[],
+            &[
+                ("XDG_CONFIG_HOME", &config_home.to_string_lossy()),
+                ("XDG_DATA_HOME", "/xdg/data"),
+            ],
+        );
+        assert_eq!(PathBuf::from("/from/file"), dir);
+    }
+
+    #[test]
+    fn env_overrides_config_file() {
+        let config_home = config_home("cvmaker_env_overrides_config_file");
+        let dir = resolve(
+            &[],
+            &[
+                ("XDG_CONFIG_HOME", &config_home.to_string_lossy()),
+                (DATA_DIR_ENV, "/from/env"),
+            ],
+        );
+        assert_eq!(PathBuf::from("/from/env"), dir);
+    }
+
+    #[test]
+    fn flag_overrides_env() {
+        let vars = [(DATA_DIR_ENV, "/from/env")];
+        assert_eq!(
+            PathBuf::from("/from/flag"),
+            resolve(&["--data-dir", "/from/flag"], &vars)
+        );
+        assert_eq!(
+            PathBuf::from("/from/flag"),
+            resolve(&["--data-dir=/from/flag"], &vars)
+        );
+    }
+
+    #[test]
+    fn corrupt_config_file_reported() {
+        let config_home = env::temp_dir().join("cvmaker_corrupt_config_file_reported");
+        fs::create_dir_all(config_home.join(APP_DIR)).unwrap();
+        fs::write(config_home.join(APP_DIR).join(CONFIG_FILE), "data_dir = ").unwrap();
+        let config_home = config_home.to_string_lossy().into_owned();
+        let res = Config::resolve_with(&[], |name| match name {
+            "XDG_CONFIG_HOME" => Some(config_home.clone()),
+            _ => None,
+        });
+        match res {
+            Err(CVError::Config { .. }) => (),
+            res => panic!("Expected the config file to be rejected, got {:?}", res),
+        }
+    }
+}
@@ -1,18 +1,339 @@
-use base::CV;
-use serde_json;
+use base::{Language, Profile, ProfilePart, CV};
+use error::{CVError, CVResult};
+use schema::{cv_from_json, profile_from_json};
+use serde::Serialize;
+use serde_json::{self, Value};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{self, Display, Formatter};
+use std::fs::{self, File};
+use std::io;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use uuid::Uuid;
 use vfs::{MemoryFS, PhysicalFS, VPath, VFS};
 
 pub type CVDao = CVManagerFileBased<PhysicalFS>;
 
+// The file the profile is stored in, next to the CVs.
+const PROFILE_FILE: &str = "profile.json";
+// The files are written under this suffix first and renamed once they are complete.
+const TMP_SUFFIX: &str = ".tmp";
+// The earlier revisions of the CVs are kept in this directory, next to the CVs.
+const HISTORY_DIR: &str = "history";
+// The number of the earlier revisions kept for each CV, the oldest ones are dropped.
+const HISTORY_LIMIT: usize = 20;
+
+// File operations the vfs crate lacks, needed to replace the stored files safely and to order
+// them by their age.
+pub trait AtomicFS: VFS {
+    // Moves the file to the new path, the file on the new path is replaced in a single step.
+    fn rename(&self, from: &str, to: &str) -> io::Result<()>;
+    // Makes sure the content of the file has reached the storage. A no-op for the backends
+    // which have nothing to flush.
+    fn sync(&self, path: &str) -> io::Result<()>;
+    // The time of the last modification of the file, None for the backends which do not keep
+    // it.
+    fn modified(&self, _path: &str) -> io::Result<Option<SystemTime>> {
+        Ok(None)
+    }
+}
+
+impl AtomicFS for PhysicalFS {
+    fn rename(&self, from: &str, to: &str) -> io::Result<()> {
+        fs::rename(from, to)?;
+        // The rename itself is durable only once the directory is synced. Not every platform
+        // can open a directory, hence the failure is not reported.
+        if let Some(dir) = Path::new(to).parent() {
+            let _ = File::open(dir).and_then(|dir| dir.sync_all());
+        }
+        Ok(())
+    }
+
+    fn sync(&self, path: &str) -> io::Result<()> {
+        File::open(path)?.sync_all()
+    }
+
+    fn modified(&self, path: &str) -> io::Result<Option<SystemTime>> {
+        fs::metadata(path)?.modified().map(Some)
+    }
+}
+
+impl AtomicFS for MemoryFS {
+    fn rename(&self, from: &str, to: &str) -> io::Result<()> {
+        let mut content = vec![];
+        self.path(from).open()?.read_to_end(&mut content)?;
+        self.path(to).create()?.write_all(&content)?;
+        self.path(from).rm()
+    }
+
+    fn sync(&self, _: &str) -> io::Result<()> {
+        Ok(())
+    }
+}
+
 // The backend for this application. The default is reading raw files from the filesystem
 // (the "primary key" is the path to the file, in this case)
 // Fs-backed backend in not performance-scalable, of course, but that's not what we want anyway.
 pub trait CVManager {
-    fn add_cv(&self, cv: &mut CV) -> Result<(), String>;
-    fn remove_cv(&self, cv: &mut CV) -> Result<(), String>;
-    fn update_cv(&self, cv: &CV) -> Result<(), String>;
-    fn read_cv(&self, file_path: &str) -> Result<CV, String>;
+    fn add_cv(&self, cv: &mut CV) -> CVResult<()>;
+    fn remove_cv(&self, cv: &mut CV) -> CVResult<()>;
+    fn update_cv(&self, cv: &CV) -> CVResult<()>;
+    fn read_cv(&self, file_path: &str) -> CVResult<CV>;
+    // Stores a copy of the stored CV under a new id, the copy gets the label if one is given.
+    fn clone_cv(&self, file_path: &str, label: Option<&str>) -> CVResult<CV>;
+    // Summaries of all the stored CVs, the most recently modified first.
+    fn list_cvs(&self) -> CVResult<CVListing>;
+    fn search_cvs(&self, filter: &CVFilter) -> CVResult<CVListing>;
+    // The profile shared by the CVs, see base::Profile.
+    fn read_profile(&self) -> CVResult<Profile>;
+    // Stores the profile and brings the CVs linked to it up to date. Returns the CVs which have
+    // changed.
+    fn update_profile(&self, profile: &Profile) -> CVResult<Vec<SyncedCV>>;
+    // The earlier revisions of the stored CV, the most recent first. A revision is kept
+    // whenever the CV is replaced with a different content.
+    fn list_revisions(&self, file_path: &str) -> CVResult<Vec<Revision>>;
+    fn read_revision(&self, file_path: &str, revision: &str) -> CVResult<CV>;
+    // The fields which differ between the revisions, the revision is compared with the current
+    // CV when there is no new one.
+    fn diff_revisions(
+        &self,
+        file_path: &str,
+        old: &str,
+        new: Option<&str>,
+    ) -> CVResult<Vec<FieldChange>>;
+    // Replaces the stored CV with the revision. The replaced content is kept as a revision
+    // too, so that the restore can be undone.
+    fn restore_revision(&self, file_path: &str, revision: &str) -> CVResult<CV>;
+}
+
+// Lightweight description of a stored CV, enough to present it in a list. The id is the path
+// the CV can be read from.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CVSummary {
+    pub id: String,
+    pub name: String,
+    pub surname: String,
+    pub label: Option<String>,
+    // Not every backend keeps the modification time of its files.
+    pub modified: Option<SystemTime>,
+}
+
+impl CVSummary {
+    pub fn new(cv: &CV, modified: Option<SystemTime>) -> CVSummary {
+        CVSummary {
+            id: cv.path.clone().unwrap_or_default(),
+            name: cv.basic.name.clone(),
+            surname: cv.basic.surname.clone(),
+            label: cv.label.clone(),
+            modified,
+        }
+    }
+
+    // Most recently modified first, the rest ordered by the name of the person.
+    fn display_order(&self, other: &CVSummary) -> Ordering {
+        other
+            .modified
+            .cmp(&self.modified)
+            .then_with(|| self.surname.cmp(&other.surname))
+            .then_with(|| self.name.cmp(&other.name))
+            .then_with(|| self.id.cmp(&other.id))
+    }
+}
+
+// The CVs found by a listing or a search. The files named as CVs which cannot be read are not
+// left out silently, they are reported along with the reason.
+#[derive(Debug, Default)]
+pub struct CVListing {
+    pub summaries: Vec<CVSummary>,
+    pub unreadable: Vec<UnreadableCV>,
+}
+
+// The path of a CV which cannot be read along with the reason.
+pub type UnreadableCV = (String, CVError);
+
+// A stored CV along with the time of its last modification, if the backend keeps it.
+type StoredCV = (CV, Option<SystemTime>);
+
+// A CV changed by the update of the profile, along with the parts of it which have changed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SyncedCV {
+    pub summary: CVSummary,
+    pub parts: Vec<ProfilePart>,
+}
+
+// An earlier content of a stored CV. The id is the time the content was replaced, in
+// milliseconds since the epoch.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Revision {
+    pub id: String,
+    pub replaced: SystemTime,
+}
+
+impl Revision {
+    fn new(millis: u64) -> Revision {
+        Revision {
+            id: millis.to_string(),
+            replaced: UNIX_EPOCH + Duration::from_millis(millis),
+        }
+    }
+}
+
+// A field of the CV which differs between two revisions. The field is named by its path in the
+// JSON, e.g. "experience[0].employer", the value is missing when the field is.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FieldChange {
+    pub field: String,
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+impl Display for FieldChange {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let show = |value: &Option<String>| match value {
+            Some(value) => format!("'{}'", value),
+            None => "(none)".to_string(),
+        };
+        write!(f, "{}: {} -> {}", self.field, show(&self.old), show(&self.new))
+    }
+}
+
+// The fields which differ between the CVs, ordered by their names. The path of the CV is not
+// compared.
+pub fn diff_cvs(old: &CV, new: &CV) -> CVResult<Vec<FieldChange>> {
+    let old_fields = cv_fields(old)?;
+    let new_fields = cv_fields(new)?;
+    let names: BTreeSet<&String> = old_fields.keys().chain(new_fields.keys()).collect();
+    Ok(names
+        .into_iter()
+        .filter(|name| old_fields.get(*name) != new_fields.get(*name))
+        .map(|name| FieldChange {
+            field: name.clone(),
+            old: old_fields.get(name).cloned(),
+            new: new_fields.get(name).cloned(),
+        }).collect())
+}
+
+fn cv_fields(cv: &CV) -> CVResult<BTreeMap<String, String>> {
+    let value = serde_json::to_value(cv)
+        .map_err(|source| CVError::Serialization { path: None, source })?;
+    let mut fields = BTreeMap::new();
+    json_fields(&value, "", &mut fields);
+    fields.remove("path");
+    Ok(fields)
+}
+
+// Collects the values in the JSON under their paths, the empty values are left out.
+fn json_fields(value: &Value, name: &str, fields: &mut BTreeMap<String, String>) {
+    match value {
+        Value::Null => (),
+        Value::String(text) => {
+            fields.insert(name.to_string(), text.clone());
+        }
+        Value::Array(items) => {
+            for (idx, item) in items.iter().enumerate() {
+                json_fields(item, &format!("{}[{}]", name, idx), fields);
+            }
+        }
+        Value::Object(map) => {
+            for (key, item) in map {
+                match name {
+                    "" => json_fields(item, key, fields),
+                    _ => json_fields(item, &format!("{}.{}", name, key), fields),
+                }
+            }
+        }
+        other => {
+            fields.insert(name.to_string(), other.to_string());
+        }
+    }
+}
+
+// Criteria the searched CVs have to match, all the criteria which are set must hold. The text
+// criteria are case insensitive and match any part of the searched text.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CVFilter {
+    // Name or surname of the person.
+    pub person_name: Option<String>,
+    pub employer: Option<String>,
+    pub language: Option<Language>,
+    // Any text in the CV.
+    pub text: Option<String>,
+}
+
+impl CVFilter {
+    pub fn matches(&self, cv: &CV) -> bool {
+        let person_name = [&cv.basic.name, &cv.basic.surname];
+        let employers: Vec<&String> = cv.experience.iter().map(|exp| &exp.employer).collect();
+        contains_any(&self.person_name, &person_name)
+            && contains_any(&self.employer, &employers)
+            && match self.language {
+                None => true,
+                Some(ref language) => cv.languages.iter().any(|lang| lang.language == *language),
+            } && contains_any(&self.text, &cv_texts(cv).iter().collect::<Vec<_>>())
+    }
+}
+
+// Whether any of the texts contains the pattern, unset pattern matches anything.
+fn contains_any(pattern: &Option<String>, texts: &[&String]) -> bool {
+    match pattern {
+        None => true,
+        Some(pattern) => {
+            let pattern = pattern.to_lowercase();
+            texts
+                .iter()
+                .any(|text| text.to_lowercase().contains(&pattern))
+        }
+    }
+}
+
+// All the texts of the CV the user may search for.
+fn cv_texts(cv: &CV) -> Vec<String> {
+    let mut texts = vec![cv.basic.name.clone(), cv.basic.surname.clone()];
+    texts.extend(cv.label.clone());
+    texts.extend(cv.basic.contacts.iter().map(|contact| contact.to_string()));
+    texts.extend(cv.basic.custom_fields.iter().map(|field| field.value.clone()));
+    for exp in &cv.experience {
+        texts.extend(vec![
+            exp.employer.clone(),
+            exp.job_name.clone(),
+            exp.description.clone(),
+        ]);
+    }
+    for edu in &cv.education {
+        texts.extend(vec![
+            edu.uni_name.clone(),
+            edu.degree.clone(),
+            edu.field_of_study.clone(),
+        ]);
+    }
+    for lang in &cv.languages {
+        texts.extend(vec![lang.language.to_string(), lang.notes.clone()]);
+        texts.extend(lang.language.endonym().map(str::to_string));
+        texts.extend(lang.certificates.iter().map(|certificate| certificate.name.clone()));
+    }
+    for project in &cv.projects {
+        texts.extend(vec![project.name.clone(), project.role.clone()]);
+        texts.extend(project.technologies.iter().cloned());
+    }
+    for publication in &cv.publications {
+        texts.extend(vec![publication.title.clone(), publication.venue.clone()]);
+        texts.extend(publication.co_authors.iter().cloned());
+    }
+    for certification in &cv.certifications {
+        texts.extend(vec![certification.name.clone(), certification.issuer.clone()]);
+    }
+    for section in &cv.custom_sections {
+        texts.push(section.title.clone());
+        for entry in &section.entries {
+            texts.extend(vec![
+                entry.heading.clone(),
+                entry.subheading.clone(),
+                entry.body.clone(),
+            ]);
+        }
+    }
+    texts
 }
 
 pub struct CVManagerFileBased<T: VFS> {
@@ -22,15 +343,21 @@ pub struct CVManagerFileBased<T: VFS> {
 
 impl<T> CVManagerFileBased<T>
 where
-    T: VFS,
+    T: AtomicFS,
 {
-    //TODO tmpfs is not a very good storage for permanent data :)
-    // for testing the program out, this should be enough though
-    pub fn new() -> CVManagerFileBased<PhysicalFS> {
-        CVManagerFileBased {
-            cvs_path: "/tmp".to_string(),
+    // Opens the storage in the given directory (see config::Config), the directory is created
+    // when it does not exist yet.
+    pub fn new(cvs_path: &str) -> CVResult<CVManagerFileBased<PhysicalFS>> {
+        let manager = CVManagerFileBased {
+            cvs_path: cvs_path.to_string(),
             backend: PhysicalFS {},
-        }
+        };
+        manager
+            .backend
+            .path(cvs_path)
+            .mkdir()
+            .map_err(|err| CVError::io(cvs_path, err))?;
+        Ok(manager)
     }
 
     //TODO use memory FS in tests
@@ -42,113 +369,385 @@ where
         }
     }
 
-    // Saves a cv as a JSON to a file.
-    // Panics:
-    //      when there is no id set in the cv attribute.
-    fn save_cv(&self, cv: &CV) -> Result<(), String> {
-        if cv.path.is_none() {
-            panic!("CV must have a valid ID to be saved.");
-        }
-        let json_str = serde_json::to_string(&cv);
-        if let Err(err) = json_str {
-            return Err(err.to_string());
-        }
-        let json_str = json_str.unwrap();
-        let path = self.backend.path(cv.path.as_ref().unwrap().to_string());
-        if let Some(p) = path.parent() {
-            return match p.mkdir() {
-                Ok(_) => {
-                    if let Ok(mut vfile) = path.create() {
-                        if let Err(err) = vfile.write(json_str.as_bytes()) {
-                            eprintln!("{}", err);
-                        }
-                    }
-                    Ok(())
+    // Saves a cv as a JSON to a file. The cv must have its id set. The content it replaces is
+    // kept as a revision.
+    fn save_cv(&self, cv: &CV) -> CVResult<()> {
+        let path = cv.path.as_ref().ok_or(CVError::NotStored)?;
+        let json_str = to_json(cv)?;
+        self.keep_revision(path, &json_str)?;
+        self.write_file(path, &json_str)
+    }
+
+    fn write_json<S: Serialize>(&self, path_str: &str, value: &S) -> CVResult<()> {
+        self.write_file(path_str, &to_json(value)?)
+    }
+
+    // The content is written into a temporary file which then replaces the original one, so
+    // that a failed write never leaves a truncated file behind.
+    fn write_file(&self, path_str: &str, json_str: &str) -> CVResult<()> {
+        let path = self.backend.path(path_str.to_string());
+        let parent = path.parent().ok_or_else(|| {
+            let source = io::Error::new(io::ErrorKind::InvalidInput, "no parent directory");
+            CVError::io(path_str, source)
+        })?;
+        let tmp_str = format!("{}{}", path_str, TMP_SUFFIX);
+        let tmp = self.backend.path(tmp_str.as_str());
+        let res = parent
+            .mkdir()
+            .and_then(|_| tmp.create())
+            .and_then(|mut vfile| {
+                vfile.write_all(json_str.as_bytes())?;
+                vfile.flush()
+            }).and_then(|_| self.backend.sync(&tmp_str))
+            .and_then(|_| self.backend.rename(&tmp_str, path_str));
+        if let Err(err) = res {
+            if tmp.exists() {
+                let _ = tmp.rm();
+            }
+            return Err(CVError::io(path_str, err));
+        }
+        Ok(())
+    }
+
+    fn profile_path(&self) -> String {
+        format!("{}/{}", self.cvs_path, PROFILE_FILE)
+    }
+
+    // The revisions of a CV are kept in the history directory next to it, each CV has its own
+    // directory named by its id.
+    fn history_dir(&self, file_path: &str) -> String {
+        let path = Path::new(file_path);
+        let parent = path.parent().map_or("".into(), |parent| parent.to_string_lossy());
+        let id = path.file_stem().map_or("".into(), |stem| stem.to_string_lossy());
+        format!("{}/{}/{}", parent.trim_end_matches('/'), HISTORY_DIR, id)
+    }
+
+    fn revision_path(&self, file_path: &str, revision: &str) -> String {
+        format!("{}/{}.json", self.history_dir(file_path), revision)
+    }
+
+    // The ids of the revisions of the CV, the most recent first.
+    fn revision_ids(&self, file_path: &str) -> CVResult<Vec<u64>> {
+        let dir_str = self.history_dir(file_path);
+        let dir = self.backend.path(dir_str.as_str());
+        if !dir.exists() {
+            return Ok(vec![]);
+        }
+        let entries = dir.read_dir().map_err(|err| CVError::io(&dir_str, err))?;
+        let mut ids: Vec<u64> = entries
+            .filter_map(|entry| entry.ok()?.file_name())
+            .filter_map(|name| name.trim_end_matches(".json").parse().ok())
+            .collect();
+        ids.sort_by(|first, second| second.cmp(first));
+        Ok(ids)
+    }
+
+    // Keeps the stored content of the CV as a revision before it is replaced with the new one.
+    // Nothing is kept when the content does not change. The oldest revisions over the limit
+    // are removed.
+    fn keep_revision(&self, file_path: &str, new_content: &str) -> CVResult<()> {
+        let path = self.backend.path(file_path);
+        if !path.exists() {
+            return Ok(());
+        }
+        let mut content = String::new();
+        path.open()
+            .and_then(|mut vfile| vfile.read_to_string(&mut content))
+            .map_err(|err| CVError::io(file_path, err))?;
+        if content == new_content {
+            return Ok(());
+        }
+        let ids = self.revision_ids(file_path)?;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since| since.as_millis() as u64);
+        // The ids have to stay unique even when the CV is saved twice within a millisecond.
+        let id = ids.first().map_or(now, |last| now.max(last + 1));
+        self.write_file(&self.revision_path(file_path, &id.to_string()), &content)?;
+        for old in ids.iter().skip(HISTORY_LIMIT - 1) {
+            let old_path = self.revision_path(file_path, &old.to_string());
+            self.backend
+                .path(old_path.as_str())
+                .rm()
+                .map_err(|err| CVError::io(&old_path, err))?;
+        }
+        Ok(())
+    }
+}
+
+impl<T> CVManagerFileBased<T>
+where
+    T: AtomicFS,
+    T::PATH: 'static,
+{
+    // All the stored CVs and the paths of the ones which cannot be read, along with the reason.
+    // Files which are not named as CVs are left out. The CVs get the path they have been read
+    // from.
+    fn stored_cvs(&self) -> CVResult<(Vec<StoredCV>, Vec<UnreadableCV>)> {
+        let entries = self
+            .backend
+            .path(self.cvs_path.as_str())
+            .read_dir()
+            .map_err(|err| CVError::io(&self.cvs_path, err))?;
+        let mut stored = vec![];
+        let mut unreadable = vec![];
+        for entry in entries.filter_map(|entry| entry.ok()) {
+            if !entry.file_name().is_some_and(|name| is_id_file_name(&name)) {
+                continue;
+            }
+            let path = entry.to_string().to_string();
+            match self.read_cv(&path) {
+                Ok(mut cv) => {
+                    cv.set_path(&*entry);
+                    // The CV has just been read, the time is merely missing from the listing
+                    // when it cannot be found out.
+                    let modified = self.backend.modified(&path).unwrap_or(None);
+                    stored.push((cv, modified));
                 }
-                Err(e) => Err(e.to_string()),
+                Err(err) => unreadable.push((path, err)),
+            }
+        }
+        Ok((stored, unreadable))
+    }
+
+    // Moves the CVs stored in the legacy directory by the older versions into this storage.
+    // Only the files named by gen_id which contain a valid CV are moved, anything else in the
+    // directory is left alone. Returns the number of CVs moved.
+    pub fn migrate_from(&self, legacy_path: &str) -> CVResult<usize> {
+        let legacy_dir = self.backend.path(legacy_path);
+        if legacy_path == self.cvs_path || !legacy_dir.exists() {
+            return Ok(0);
+        }
+        let entries = legacy_dir
+            .read_dir()
+            .map_err(|err| CVError::io(legacy_path, err))?;
+        let mut migrated = 0;
+        for entry in entries.filter_map(|entry| entry.ok()) {
+            let is_cv_file = entry.file_name().is_some_and(|name| is_id_file_name(&name));
+            if !is_cv_file {
+                continue;
+            }
+            let old_path = entry.to_string().into_owned();
+            let mut cv = match self.read_cv(&old_path) {
+                Ok(cv) => cv,
+                Err(_) => continue,
             };
+            let new_path = format!("{}/{}", self.cvs_path, entry.file_name().unwrap());
+            cv.set_path(&self.backend.path(new_path));
+            self.save_cv(&cv)?;
+            entry.rm().map_err(|err| CVError::io(&old_path, err))?;
+            migrated += 1;
         }
-        Err("Wrong path given.".to_string())
+        Ok(migrated)
     }
 }
 
+fn to_json<S: Serialize>(value: &S) -> CVResult<String> {
+    serde_json::to_string(value).map_err(|source| CVError::Serialization { path: None, source })
+}
+
 // Generates a unique id. The implementation is based on the UUID concept.
 fn gen_id() -> String {
     Uuid::new_v4().simple().to_string()
 }
 
+// Whether the file name is the one given to the CVs on their creation, "<id>.json".
+pub fn is_id_file_name(name: &str) -> bool {
+    match name.split('.').collect::<Vec<_>>().as_slice() {
+        [id, "json"] => id.len() == 32 && id.chars().all(|c| c.is_ascii_hexdigit()),
+        _ => false,
+    }
+}
+
 // TODO: deal with duplicates (which are VERY rare to occur, but possible nevertheless!)
 // TODO: only UNIX path separators
 impl<T> CVManager for CVManagerFileBased<T>
 where
-    T: VFS,
+    T: AtomicFS,
     T::PATH: 'static,
 {
-    fn add_cv(&self, cv: &mut CV) -> Result<(), String> {
+    fn add_cv(&self, cv: &mut CV) -> CVResult<()> {
         match cv.path {
-            Some(_) => Err("Cannot add a CV which already has an ID.".to_string()),
+            Some(_) => Err(CVError::AlreadyStored),
             None => {
                 let mut id = self.cvs_path.to_string();
                 id.push_str("/");
                 id.push_str(&gen_id());
                 id.push_str(".json");
                 cv.set_path(&self.backend.path(id));
-                self.save_cv(&cv)
+                let res = self.save_cv(cv);
+                if res.is_err() {
+                    cv.path = None;
+                }
+                res
             }
         }
     }
 
-    fn remove_cv(&self, cv: &mut CV) -> Result<(), String> {
+    fn remove_cv(&self, cv: &mut CV) -> CVResult<()> {
         match &cv.path {
-            None => Err("Cannot remove a CV which has no ID.".to_string()),
+            None => Err(CVError::NotStored),
             Some(path) => {
                 let path_str = path.as_str();
                 let path = self.backend.path(path_str);
                 if !path.exists() {
-                    return Err(format!("The path '{}' does not exist.", path_str));
+                    let source = io::Error::new(io::ErrorKind::NotFound, "no such file");
+                    return Err(CVError::io(path_str, source));
                 }
-                match path.rm() {
-                    Err(_) => Err(format!("Could not remove the file: '{}'", path_str)),
-                    Ok(_) => Ok(()),
+                path.rm().map_err(|err| CVError::io(path_str, err))?;
+                let history_str = self.history_dir(path_str);
+                let history = self.backend.path(history_str.as_str());
+                if history.exists() {
+                    history.rmrf().map_err(|err| CVError::io(&history_str, err))?;
                 }
+                Ok(())
             }
         }
     }
 
-    fn update_cv(&self, cv: &CV) -> Result<(), String> {
+    fn update_cv(&self, cv: &CV) -> CVResult<()> {
         match &cv.path {
-            None => Err("Cannot update a CV which has no ID.".to_string()),
-            Some(_) => self.save_cv(&cv),
+            None => Err(CVError::NotStored),
+            Some(_) => self.save_cv(cv),
         }
     }
 
-    fn read_cv(&self, file_path: &str) -> Result<CV, String> {
-        let path = self.backend.path(file_path);
-        match path.open() {
-            Err(_) => Err(format!("Couldn't open file {}", file_path)),
-            Ok(mut vfile) => {
-                let mut buff = String::new();
-                match &vfile.read_to_string(&mut buff) {
-                    Err(_) => Err(format!("Coudn't read file {}", file_path)),
-                    Ok(_) => match serde_json::from_str(&buff) {
-                        Ok(cv) => Ok(cv),
-                        Err(err) => Err(err.to_string()),
-                    },
-                }
+    fn list_cvs(&self) -> CVResult<CVListing> {
+        self.search_cvs(&CVFilter::default())
+    }
+
+    fn search_cvs(&self, filter: &CVFilter) -> CVResult<CVListing> {
+        let (stored, unreadable) = self.stored_cvs()?;
+        let mut summaries: Vec<CVSummary> = stored
+            .into_iter()
+            .filter(|(cv, _)| filter.matches(cv))
+            .map(|(cv, modified)| CVSummary::new(&cv, modified))
+            .collect();
+        summaries.sort_by(|first, second| first.display_order(second));
+        Ok(CVListing {
+            summaries,
+            unreadable,
+        })
+    }
+
+    fn read_cv(&self, file_path: &str) -> CVResult<CV> {
+        let mut buff = String::new();
+        self.backend
+            .path(file_path)
+            .open()
+            .and_then(|mut vfile| vfile.read_to_string(&mut buff))
+            .map_err(|err| CVError::io(file_path, err))?;
+        cv_from_json(&buff, Some(file_path))
+    }
+
+    fn clone_cv(&self, file_path: &str, label: Option<&str>) -> CVResult<CV> {
+        let mut copy = self.read_cv(file_path)?;
+        copy.path = None;
+        if let Some(label) = label {
+            copy.label = Some(label.to_string());
+        }
+        self.add_cv(&mut copy)?;
+        Ok(copy)
+    }
+
+    fn read_profile(&self) -> CVResult<Profile> {
+        let path_str = self.profile_path();
+        let path = self.backend.path(path_str.as_str());
+        if !path.exists() {
+            return Err(CVError::NoProfile);
+        }
+        let mut buff = String::new();
+        path.open()
+            .and_then(|mut vfile| vfile.read_to_string(&mut buff))
+            .map_err(|err| CVError::io(&path_str, err))?;
+        profile_from_json(&buff, Some(&path_str))
+    }
+
+    // The CVs which cannot be read are left as they are.
+    fn update_profile(&self, profile: &Profile) -> CVResult<Vec<SyncedCV>> {
+        self.write_json(&self.profile_path(), profile)?;
+        let mut synced = vec![];
+        for (mut cv, _) in self.stored_cvs()?.0 {
+            let parts = cv.sync_with(profile);
+            if parts.is_empty() {
+                continue;
             }
+            self.save_cv(&cv)?;
+            synced.push(SyncedCV {
+                summary: CVSummary::new(&cv, None),
+                parts,
+            });
+        }
+        Ok(synced)
+    }
+
+    fn list_revisions(&self, file_path: &str) -> CVResult<Vec<Revision>> {
+        Ok(self
+            .revision_ids(file_path)?
+            .into_iter()
+            .map(Revision::new)
+            .collect())
+    }
+
+    // The revision keeps the path of the CV it belongs to.
+    fn read_revision(&self, file_path: &str, revision: &str) -> CVResult<CV> {
+        let no_revision = || CVError::NoRevision(revision.to_string());
+        revision.parse::<u64>().map_err(|_| no_revision())?;
+        let path = self.revision_path(file_path, revision);
+        if !self.backend.path(path.as_str()).exists() {
+            return Err(no_revision());
         }
+        let mut cv = self.read_cv(&path)?;
+        cv.path = Some(file_path.to_string());
+        Ok(cv)
+    }
+
+    fn diff_revisions(
+        &self,
+        file_path: &str,
+        old: &str,
+        new: Option<&str>,
+    ) -> CVResult<Vec<FieldChange>> {
+        let old = self.read_revision(file_path, old)?;
+        let new = match new {
+            Some(new) => self.read_revision(file_path, new)?,
+            None => self.read_cv(file_path)?,
+        };
+        diff_cvs(&old, &new)
+    }
+
+    fn restore_revision(&self, file_path: &str, revision: &str) -> CVResult<CV> {
+        let cv = self.read_revision(file_path, revision)?;
+        self.save_cv(&cv)?;
+        Ok(cv)
     }
 }
 
 #[cfg(test)]
 #[allow(unused_variables)]
 mod tests {
-    use super::{gen_id, CVManagerFileBased};
-    use base::test::basic_cv_factory;
+    use super::{
+        diff_cvs, gen_id, is_id_file_name, AtomicFS, CVFilter, CVManagerFileBased, FieldChange,
+        HISTORY_LIMIT, TMP_SUFFIX,
+    };
+    use base::test::{
+        assessed_lang_factory, basic_cv_factory, certification_factory, custom_section_factory,
+        experience_factory, project_factory, publication_factory,
+    };
+    use base::{Lang, Language, LanguageProficiency, Profile, ProfileLink, ProfilePart, CV};
     use dao::CVManager;
+    use error::CVError;
+    use std::borrow::Cow;
     use std::collections::HashSet;
-    use vfs::{MemoryFS, VPath, VFS};
+    use std::env;
+    use std::fs;
+    use std::io::{self, Read, Seek, SeekFrom, Write};
+    use std::path::PathBuf;
+    use std::sync::{Arc, Mutex};
+    use vfs::memory::{MemoryFile, MemoryMetadata, MemoryPath};
+    use vfs::{MemoryFS, OpenOptions, PhysicalFS, VFile, VMetadata, VPath, VFS};
     type CVDao = CVManagerFileBased<MemoryFS>;
+    type PhysicalDao = CVManagerFileBased<PhysicalFS>;
     #[test]
     fn test_id_gen_uniqueness() {
         let iterations = 1000;
@@ -159,16 +758,23 @@ mod tests {
         assert!(ids.len() >= (iterations - 1));
     }
 
+    #[test]
+    fn generated_ids_recognized() {
+        assert!(is_id_file_name(&format!("{}.json", gen_id())));
+        assert!(!is_id_file_name("notes.json"));
+        assert!(!is_id_file_name(&format!("{}.pdf", gen_id())));
+    }
+
     #[test]
     fn fail_when_add_cv_with_id() {
         let fs = MemoryFS::new();
         let manager = CVDao::new_testing();
         let mut cv = basic_cv_factory();
         cv.set_path(&fs.path(""));
-        assert_eq!(
-            Err("Cannot add a CV which already has an ID.".to_string()),
-            manager.add_cv(&mut cv)
-        );
+        match manager.add_cv(&mut cv) {
+            Err(CVError::AlreadyStored) => (),
+            res => panic!("Expected the CV to be refused, got {:?}", res),
+        }
     }
 
     #[test]
@@ -198,9 +804,9 @@ mod tests {
     fn remove_cv_happy_scenario() {
         let manager = CVDao::new_testing();
         let mut cv = basic_cv_factory();
-        assert_eq!(Ok(()), manager.add_cv(&mut cv));
+        manager.add_cv(&mut cv).unwrap();
         let before = count_num_files(&manager);
-        assert_eq!(Ok(()), manager.remove_cv(&mut cv));
+        manager.remove_cv(&mut cv).unwrap();
         let after = count_num_files(&manager);
         assert_ne!(before, after);
     }
@@ -209,10 +815,10 @@ mod tests {
     fn remove_cv_no_id() {
         let manager = CVDao::new_testing();
         let mut cv = basic_cv_factory();
-        assert_eq!(
-            Err("Cannot remove a CV which has no ID.".to_string()),
-            manager.remove_cv(&mut cv)
-        );
+        match manager.remove_cv(&mut cv) {
+            Err(CVError::NotStored) => (),
+            res => panic!("Expected the CV to be refused, got {:?}", res),
+        }
     }
 
     #[test]
@@ -220,20 +826,23 @@ mod tests {
         let manager = CVDao::new_testing();
         let mut cv = basic_cv_factory();
         cv.set_path(&manager.backend.path("/foobar"));
-        assert_eq!(
-            Err("The path '/foobar' does not exist.".to_string()),
-            manager.remove_cv(&mut cv)
-        );
+        match manager.remove_cv(&mut cv) {
+            Err(CVError::Io { path, source }) => {
+                assert_eq!("/foobar", path);
+                assert_eq!(io::ErrorKind::NotFound, source.kind());
+            }
+            res => panic!("Expected a missing file, got {:?}", res),
+        }
     }
 
     #[test]
     fn update_cv_no_id() {
         let manager = CVDao::new_testing();
         let cv = basic_cv_factory();
-        assert_eq!(
-            Err("Cannot update a CV which has no ID.".to_string()),
-            manager.update_cv(&cv)
-        );
+        match manager.update_cv(&cv) {
+            Err(CVError::NotStored) => (),
+            res => panic!("Expected the CV to be refused, got {:?}", res),
+        }
     }
 
     #[test]
@@ -246,9 +855,666 @@ mod tests {
         let manager = CVDao::new_testing();
         let mut cv = basic_cv_factory();
         let mut cv_copy = basic_cv_factory();
-        assert_eq!(Ok(()), manager.add_cv(&mut cv));
+        manager.add_cv(&mut cv).unwrap();
         cv_copy.path = cv.path.clone();
-        assert_eq!(Ok(cv_copy), manager.read_cv(&cv.path.unwrap()));
+        assert_eq!(cv_copy, manager.read_cv(&cv.path.unwrap()).unwrap());
+    }
+
+    #[test]
+    fn clone_cv_gets_new_id_and_label() {
+        let manager = CVDao::new_testing();
+        let mut cv = basic_cv_factory();
+        cv.experience = vec![experience_factory()];
+        manager.add_cv(&mut cv).unwrap();
+        let original = cv.path.clone().unwrap();
+        let copy = manager.clone_cv(&original, Some("Translators")).unwrap();
+        assert_ne!(cv.path, copy.path);
+        assert_eq!(Some("Translators".to_string()), copy.label);
+        assert_eq!(cv.experience, copy.experience);
+        assert_eq!(copy, manager.read_cv(copy.path.as_ref().unwrap()).unwrap());
+        // The original is left untouched.
+        assert_eq!(cv, manager.read_cv(&original).unwrap());
+        let unlabelled = manager.clone_cv(copy.path.as_ref().unwrap(), None).unwrap();
+        assert_eq!(copy.label, unlabelled.label);
+    }
+
+    #[test]
+    fn read_profile_before_stored() {
+        match CVDao::new_testing().read_profile() {
+            Err(CVError::NoProfile) => (),
+            res => panic!("Expected no profile, got {:?}", res),
+        }
+    }
+
+    #[test]
+    fn update_profile_syncs_linked_cvs() {
+        let manager = manager_with_cvs();
+        let mut linked = basic_cv_factory();
+        linked.profile = Some(ProfileLink::default());
+        manager.add_cv(&mut linked).unwrap();
+        let mut profile = Profile::from_cv(&linked);
+        manager.update_profile(&profile).unwrap();
+        assert_eq!(profile, manager.read_profile().unwrap());
+        profile.basic.surname = "Romanovich".to_string();
+        let synced = manager.update_profile(&profile).unwrap();
+        assert_eq!(1, synced.len());
+        assert_eq!(linked.path, Some(synced[0].summary.id.clone()));
+        assert_eq!(vec![ProfilePart::Name], synced[0].parts);
+        let stored = manager.read_cv(linked.path.as_ref().unwrap()).unwrap();
+        assert_eq!("Romanovich", stored.basic.surname);
+        // The CVs which are not linked keep their data.
+        let filter = CVFilter {
+            person_name: Some("Raskolnikov".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(vec!["Peter"], searched_names(&manager, filter));
+        assert!(manager.update_profile(&profile).unwrap().is_empty());
+    }
+
+    #[test]
+    fn clone_cv_missing() {
+        let manager = CVDao::new_testing();
+        match manager.clone_cv("/missing.json", None) {
+            Err(CVError::Io { .. }) => (),
+            res => panic!("Expected the missing CV to be reported, got {:?}", res),
+        }
+    }
+
+    // Stores the CV and updates it with the surnames one by one, returns the updated CV.
+    fn cv_with_revisions(manager: &CVDao, surnames: &[&str]) -> CV {
+        let mut cv = basic_cv_factory();
+        manager.add_cv(&mut cv).unwrap();
+        for surname in surnames {
+            cv.basic.surname = surname.to_string();
+            manager.update_cv(&cv).unwrap();
+        }
+        cv
+    }
+
+    #[test]
+    fn update_cv_keeps_revisions() {
+        let manager = CVDao::new_testing();
+        let cv = cv_with_revisions(&manager, &["Romanovich", "Romanov"]);
+        let path = cv.path.clone().unwrap();
+        // Saving the same content again keeps no revision.
+        manager.update_cv(&cv).unwrap();
+        let revisions = manager.list_revisions(&path).unwrap();
+        assert_eq!(2, revisions.len());
+        assert!(revisions[0].replaced > revisions[1].replaced);
+        let latest = manager.read_revision(&path, &revisions[0].id).unwrap();
+        assert_eq!("Romanovich", latest.basic.surname);
+        assert_eq!(cv.path, latest.path);
+        let first = manager.read_revision(&path, &revisions[1].id).unwrap();
+        assert_eq!("Raskolnikov", first.basic.surname);
+    }
+
+    #[test]
+    fn history_is_bounded() {
+        let manager = CVDao::new_testing();
+        let surnames: Vec<String> = (0..HISTORY_LIMIT + 5).map(|i| i.to_string()).collect();
+        let surnames: Vec<&str> = surnames.iter().map(|surname| surname.as_str()).collect();
+        let cv = cv_with_revisions(&manager, &surnames);
+        let path = cv.path.unwrap();
+        let revisions = manager.list_revisions(&path).unwrap();
+        assert_eq!(HISTORY_LIMIT, revisions.len());
+        let oldest = manager.read_revision(&path, &revisions[HISTORY_LIMIT - 1].id);
+        assert_eq!("4", oldest.unwrap().basic.surname);
     }
 
+    #[test]
+    fn diff_revision_with_current_cv() {
+        let manager = CVDao::new_testing();
+        let mut cv = cv_with_revisions(&manager, &["Romanovich"]);
+        let path = cv.path.clone().unwrap();
+        cv.label = Some("Accounting".to_string());
+        manager.update_cv(&cv).unwrap();
+        let revisions = manager.list_revisions(&path).unwrap();
+        let changes = manager
+            .diff_revisions(&path, &revisions[1].id, None)
+            .unwrap();
+        let expected = vec![
+            FieldChange {
+                field: "basic.surname".to_string(),
+                old: Some("Raskolnikov".to_string()),
+                new: Some("Romanovich".to_string()),
+            },
+            FieldChange {
+                field: "label".to_string(),
+                old: None,
+                new: Some("Accounting".to_string()),
+            },
+        ];
+        assert_eq!(expected, changes);
+        assert_eq!("label: (none) -> 'Accounting'", changes[1].to_string());
+        let changes = manager
+            .diff_revisions(&path, &revisions[1].id, Some(&revisions[0].id))
+            .unwrap();
+        assert_eq!(expected[..1], changes[..]);
+    }
+
+    #[test]
+    fn diff_cvs_by_field() {
+        let cv = basic_cv_factory();
+        let mut changed = cv.clone();
+        changed.experience = vec![experience_factory()];
+        changed.path = Some("/elsewhere.json".to_string());
+        assert!(diff_cvs(&cv, &cv).unwrap().is_empty());
+        let changes = diff_cvs(&cv, &changed).unwrap();
+        assert!(!changes.is_empty());
+        for change in changes {
+            assert!(change.field.starts_with("experience[0]."));
+            assert_eq!(None, change.old);
+        }
+    }
+
+    #[test]
+    fn restore_revision_can_be_undone() {
+        let manager = CVDao::new_testing();
+        let cv = cv_with_revisions(&manager, &["Romanovich"]);
+        let path = cv.path.clone().unwrap();
+        let revision = manager.list_revisions(&path).unwrap()[0].id.clone();
+        let restored = manager.restore_revision(&path, &revision).unwrap();
+        assert_eq!("Raskolnikov", restored.basic.surname);
+        assert_eq!(restored, manager.read_cv(&path).unwrap());
+        let revisions = manager.list_revisions(&path).unwrap();
+        assert_eq!(2, revisions.len());
+        manager.restore_revision(&path, &revisions[0].id).unwrap();
+        assert_eq!(cv, manager.read_cv(&path).unwrap());
+    }
+
+    #[test]
+    fn unknown_revision() {
+        let manager = CVDao::new_testing();
+        let cv = cv_with_revisions(&manager, &[]);
+        let path = cv.path.unwrap();
+        for revision in &["123", "../profile"] {
+            match manager.read_revision(&path, revision) {
+                Err(CVError::NoRevision(id)) => assert_eq!(revision, &id),
+                res => panic!("Expected the revision to be missing, got {:?}", res),
+            }
+        }
+    }
+
+    #[test]
+    fn remove_cv_removes_history() {
+        let manager = CVDao::new_testing();
+        let mut cv = cv_with_revisions(&manager, &["Romanovich"]);
+        let path = cv.path.clone().unwrap();
+        manager.remove_cv(&mut cv).unwrap();
+        assert!(!manager.backend.path(manager.history_dir(&path)).exists());
+        assert!(manager.list_revisions(&path).unwrap().is_empty());
+    }
+
+    // Stores the CV the way the older versions did, directly in the legacy directory.
+    fn store_legacy_cv(manager: &CVDao, legacy_dir: &str) -> String {
+        let mut cv = basic_cv_factory();
+        let path = format!("{}/{}.json", legacy_dir, gen_id());
+        cv.set_path(&manager.backend.path(path.as_str()));
+        manager.save_cv(&cv).unwrap();
+        path
+    }
+
+    #[test]
+    fn migrate_legacy_cvs() {
+        let manager = CVManagerFileBased {
+            cvs_path: "/data".to_string(),
+            backend: MemoryFS::new(),
+        };
+        let old_path = store_legacy_cv(&manager, "/tmp");
+        let mut other = manager.backend.path("/tmp/notes.json").create().unwrap();
+        other.write_all(b"{}").unwrap();
+        drop(other);
+
+        assert_eq!(1, manager.migrate_from("/tmp").unwrap());
+        assert!(!manager.backend.path(old_path.as_str()).exists());
+        assert!(manager.backend.path("/tmp/notes.json").exists());
+        let new_path = old_path.replace("/tmp", "/data");
+        let cv = manager.read_cv(&new_path).unwrap();
+        assert_eq!(Some(new_path), cv.path);
+    }
+
+    #[test]
+    fn migrate_nothing_when_legacy_dir_used() {
+        let manager = CVManagerFileBased {
+            cvs_path: "/tmp".to_string(),
+            backend: MemoryFS::new(),
+        };
+        store_legacy_cv(&manager, "/tmp");
+        assert_eq!(0, manager.migrate_from("/tmp").unwrap());
+        assert_eq!(0, manager.migrate_from("/nonexistent").unwrap());
+    }
+
+    // Stores CVs of two people: Peter Raskolnikov, an accountant speaking Russian, and Sofia
+    // Marmeladova with no experience.
+    fn manager_with_cvs() -> CVDao {
+        let manager = CVDao::new_testing();
+        let mut peter = basic_cv_factory();
+        peter.experience = vec![experience_factory()];
+        peter.languages = vec![Lang {
+            language: Language::from_code("rus").unwrap(),
+            proficiency: LanguageProficiency::C2,
+            notes: "native speaker".to_string(),
+            skills: None,
+            certificates: vec![],
+        }];
+        manager.add_cv(&mut peter).unwrap();
+        let mut sofia = basic_cv_factory();
+        sofia.basic.name = "Sofia".to_string();
+        sofia.basic.surname = "Marmeladova".to_string();
+        manager.add_cv(&mut sofia).unwrap();
+        manager
+    }
+
+    fn searched_names(manager: &CVDao, filter: CVFilter) -> Vec<String> {
+        manager
+            .search_cvs(&filter)
+            .unwrap()
+            .summaries
+            .into_iter()
+            .map(|summary| summary.name)
+            .collect()
+    }
+
+    #[test]
+    fn list_cvs_returns_summaries() {
+        let manager = manager_with_cvs();
+        let summaries = manager.list_cvs().unwrap().summaries;
+        assert_eq!(2, summaries.len());
+        // Without modification times the CVs are ordered by the surname.
+        assert_eq!("Marmeladova", summaries[0].surname);
+        assert_eq!("Raskolnikov", summaries[1].surname);
+        for summary in summaries {
+            let cv: CV = manager.read_cv(&summary.id).unwrap();
+            assert_eq!(cv.basic.name, summary.name);
+        }
+    }
+
+    #[test]
+    fn list_cvs_on_physical_fs() {
+        let dir = env::temp_dir().join(format!("cvmaker_list_{}", gen_id()));
+        let manager = PhysicalDao::new(&dir.to_string_lossy()).unwrap();
+        let mut cv = basic_cv_factory();
+        manager.add_cv(&mut cv).unwrap();
+        let summaries = manager.list_cvs().unwrap().summaries;
+        assert_eq!(1, summaries.len());
+        assert_eq!(cv.path, Some(summaries[0].id.clone()));
+        assert!(summaries[0].modified.is_some());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn list_cvs_skips_other_files() {
+        let manager = manager_with_cvs();
+        let mut other = manager.backend.path("/notes.json").create().unwrap();
+        other.write_all(b"{}").unwrap();
+        let listing = manager.list_cvs().unwrap();
+        assert_eq!(2, listing.summaries.len());
+        assert!(listing.unreadable.is_empty());
+    }
+
+    #[test]
+    fn list_cvs_reports_unreadable() {
+        let manager = manager_with_cvs();
+        let path = format!("/{}.json", gen_id());
+        let mut corrupt = manager.backend.path(path.as_str()).create().unwrap();
+        corrupt.write_all(b"{ not json").unwrap();
+        drop(corrupt);
+        let listing = manager.list_cvs().unwrap();
+        assert_eq!(2, listing.summaries.len());
+        match listing.unreadable.as_slice() {
+            [(unreadable, CVError::Serialization { path: Some(reported), .. })] => {
+                assert_eq!(&path, unreadable);
+                assert_eq!(&path, reported);
+            }
+            unreadable => panic!("Expected the corrupt CV, got {:?}", unreadable),
+        }
+    }
+
+    #[test]
+    fn search_cvs_by_person_name() {
+        let manager = manager_with_cvs();
+        let filter = CVFilter {
+            person_name: Some("marmel".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(vec!["Sofia"], searched_names(&manager, filter));
+    }
+
+    #[test]
+    fn search_cvs_by_employer_and_language() {
+        let manager = manager_with_cvs();
+        let filter = CVFilter {
+            employer: Some("abc".to_string()),
+            language: Some(Language::from_code("rus").unwrap()),
+            ..Default::default()
+        };
+        assert_eq!(vec!["Peter"], searched_names(&manager, filter));
+        let filter = CVFilter {
+            employer: Some("abc".to_string()),
+            language: Some(Language::from_code("eng").unwrap()),
+            ..Default::default()
+        };
+        assert!(searched_names(&manager, filter).is_empty());
+    }
+
+    #[test]
+    fn search_cvs_by_free_text() {
+        let manager = manager_with_cvs();
+        let filter = CVFilter {
+            text: Some("NATIVE".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(vec!["Peter"], searched_names(&manager, filter));
+        let filter = CVFilter {
+            text: Some("raskolnikov.ru".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(2, searched_names(&manager, filter).len());
+    }
+
+    #[test]
+    fn search_cvs_by_other_sections() {
+        let manager = manager_with_cvs();
+        let mut dunya = basic_cv_factory();
+        dunya.basic.name = "Dunya".to_string();
+        dunya.projects = vec![project_factory()];
+        dunya.publications = vec![publication_factory()];
+        dunya.certifications = vec![certification_factory()];
+        dunya.custom_sections = vec![custom_section_factory()];
+        dunya.languages = vec![assessed_lang_factory()];
+        manager.add_cv(&mut dunya).unwrap();
+        let texts = ["cursive", "messenger", "marmeladova", "acca", "chess club", "testdaf"];
+        for text in &texts {
+            let filter = CVFilter {
+                text: Some(text.to_string()),
+                ..Default::default()
+            };
+            assert!(searched_names(&manager, filter).contains(&"Dunya".to_string()));
+        }
+    }
+
+    #[test]
+    fn read_cv_missing_file() {
+        let manager = CVDao::new_testing();
+        match manager.read_cv("/foobar.json") {
+            Err(CVError::Io { path, .. }) => assert_eq!("/foobar.json", path),
+            res => panic!("Expected a missing file, got {:?}", res),
+        }
+    }
+
+    #[test]
+    fn read_cv_corrupt_json() {
+        let manager = CVDao::new_testing();
+        let mut file = manager.backend.path("/corrupt.json").create().unwrap();
+        file.write_all(b"{ not json").unwrap();
+        drop(file);
+        match manager.read_cv("/corrupt.json") {
+            Err(CVError::Serialization { path, .. }) => {
+                assert_eq!(Some("/corrupt.json".to_string()), path)
+            }
+            res => panic!("Expected a corrupt file, got {:?}", res),
+        }
+    }
+
+    // What the FailingFS fails on.
+    #[derive(Clone, Copy, Debug, Default, PartialEq)]
+    enum Failure {
+        #[default]
+        Nothing,
+        // The writes fail once the given number of bytes has been written.
+        WriteAfter(usize),
+        Rename,
+    }
+
+    // The failure applies to every file unless it targets a single one.
+    #[derive(Clone, Debug, Default)]
+    struct Injection {
+        failure: Failure,
+        target: Option<String>,
+    }
+
+    impl Injection {
+        // The target covers its temporary file too.
+        fn failure_at(&self, path: &str) -> Failure {
+            match self.target {
+                Some(ref target) if path.trim_end_matches(TMP_SUFFIX) != target => {
+                    Failure::Nothing
+                }
+                _ => self.failure,
+            }
+        }
+    }
+
+    // MemoryFS failing on demand, so that the failures can be shown not to damage the data.
+    struct FailingFS {
+        inner: MemoryFS,
+        injection: Arc<Mutex<Injection>>,
+    }
+
+    #[derive(Debug)]
+    struct FailingPath {
+        inner: MemoryPath,
+        injection: Arc<Mutex<Injection>>,
+    }
+
+    #[derive(Debug)]
+    struct FailingFile {
+        inner: Box<dyn VFile>,
+        // Number of bytes which can still be written, unlimited when None.
+        budget: Option<usize>,
+    }
+
+    impl FailingFS {
+        fn fail_on(&self, failure: Failure) {
+            *self.injection.lock().unwrap() = Injection {
+                failure,
+                target: None,
+            };
+        }
+
+        fn fail_at(&self, failure: Failure, path: &str) {
+            *self.injection.lock().unwrap() = Injection {
+                failure,
+                target: Some(path.to_string()),
+            };
+        }
+    }
+
+    impl VFS for FailingFS {
+        type PATH = FailingPath;
+        type FILE = MemoryFile;
+        type METADATA = MemoryMetadata;
+
+        fn path<T: Into<String>>(&self, path: T) -> FailingPath {
+            FailingPath {
+                inner: self.inner.path(path),
+                injection: self.injection.clone(),
+            }
+        }
+    }
+
+    impl AtomicFS for FailingFS {
+        fn rename(&self, from: &str, to: &str) -> io::Result<()> {
+            if self.injection.lock().unwrap().failure_at(to) == Failure::Rename {
+                return Err(io::Error::other("rename failed"));
+            }
+            self.inner.rename(from, to)
+        }
+
+        fn sync(&self, path: &str) -> io::Result<()> {
+            self.inner.sync(path)
+        }
+    }
+
+    impl VPath for FailingPath {
+        fn open_with_options(&self, options: &OpenOptions) -> io::Result<Box<dyn VFile>> {
+            let path = self.inner.to_string();
+            let budget = match self.injection.lock().unwrap().failure_at(&path) {
+                Failure::WriteAfter(bytes) => Some(bytes),
+                _ => None,
+            };
+            Ok(Box::new(FailingFile {
+                inner: self.inner.open_with_options(options)?,
+                budget,
+            }))
+        }
+
+        fn mkdir(&self) -> io::Result<()> {
+            self.inner.mkdir()
+        }
+
+        fn rm(&self) -> io::Result<()> {
+            self.inner.rm()
+        }
+
+        fn rmrf(&self) -> io::Result<()> {
+            self.inner.rmrf()
+        }
+
+        fn file_name(&self) -> Option<String> {
+            self.inner.file_name()
+        }
+
+        fn extension(&self) -> Option<String> {
+            self.inner.extension()
+        }
+
+        fn resolve(&self, path: &String) -> Box<dyn VPath> {
+            self.inner.resolve(path)
+        }
+
+        fn parent(&self) -> Option<Box<dyn VPath>> {
+            self.inner.parent()
+        }
+
+        fn exists(&self) -> bool {
+            self.inner.exists()
+        }
+
+        fn metadata(&self) -> io::Result<Box<dyn VMetadata>> {
+            self.inner.metadata()
+        }
+
+        fn read_dir(&self) -> io::Result<Box<dyn Iterator<Item = io::Result<Box<dyn VPath>>>>> {
+            self.inner.read_dir()
+        }
+
+        fn to_string(&self) -> Cow<'_, str> {
+            self.inner.to_string()
+        }
+
+        fn to_path_buf(&self) -> Option<PathBuf> {
+            self.inner.to_path_buf()
+        }
+
+        fn box_clone(&self) -> Box<dyn VPath> {
+            Box::new(FailingPath {
+                inner: self.inner.clone(),
+                injection: self.injection.clone(),
+            })
+        }
+    }
+
+    impl Read for FailingFile {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.inner.read(buf)
+        }
+    }
+
+    impl Write for FailingFile {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            let len = match self.budget {
+                Some(0) => return Err(io::Error::other("disk full")),
+                Some(budget) => budget.min(buf.len()),
+                None => buf.len(),
+            };
+            let written = self.inner.write(&buf[..len])?;
+            self.budget = self.budget.map(|budget| budget - written);
+            Ok(written)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            self.inner.flush()
+        }
+    }
+
+    impl Seek for FailingFile {
+        fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+            self.inner.seek(pos)
+        }
+    }
+
+    fn failing_manager() -> CVManagerFileBased<FailingFS> {
+        CVManagerFileBased {
+            cvs_path: "/cvs".to_string(),
+            backend: FailingFS {
+                inner: MemoryFS::new(),
+                injection: Arc::new(Mutex::new(Injection::default())),
+            },
+        }
+    }
+
+    // Stores the CV, then fails to replace it on update and checks that the stored CV has
+    // survived. Only the CV file fails, its revision is written as usual.
+    fn assert_failed_update_keeps_cv(failure: Failure) {
+        let manager = failing_manager();
+        let mut cv = basic_cv_factory();
+        manager.add_cv(&mut cv).unwrap();
+        let mut changed = cv.clone();
+        changed.experience = vec![experience_factory()];
+        let path = cv.path.clone().unwrap();
+        manager.backend.fail_at(failure, &path);
+        match manager.update_cv(&changed) {
+            Err(CVError::Io { path: failed, .. }) => assert_eq!(path, failed),
+            res => panic!("Expected the update to fail, got {:?}", res),
+        }
+        manager.backend.fail_on(Failure::Nothing);
+        assert_eq!(cv, manager.read_cv(&path).unwrap());
+        assert!(!manager.backend.path(path + TMP_SUFFIX).exists());
+    }
+
+    #[test]
+    fn failed_write_keeps_stored_cv() {
+        assert_failed_update_keeps_cv(Failure::WriteAfter(0));
+        assert_failed_update_keeps_cv(Failure::WriteAfter(10));
+    }
+
+    #[test]
+    fn failed_rename_keeps_stored_cv() {
+        assert_failed_update_keeps_cv(Failure::Rename);
+    }
+
+    #[test]
+    fn failed_revision_keeps_stored_cv() {
+        let manager = failing_manager();
+        let mut cv = basic_cv_factory();
+        manager.add_cv(&mut cv).unwrap();
+        let mut changed = cv.clone();
+        changed.experience = vec![experience_factory()];
+        let path = cv.path.clone().unwrap();
+        // The stored content is kept as a revision before the CV is replaced.
+        manager.backend.fail_on(Failure::WriteAfter(10));
+        match manager.update_cv(&changed) {
+            Err(CVError::Io { path: failed, .. }) => {
+                assert!(failed.starts_with(&manager.history_dir(&path)))
+            }
+            res => panic!("Expected the update to fail, got {:?}", res),
+        }
+        manager.backend.fail_on(Failure::Nothing);
+        assert_eq!(cv, manager.read_cv(&path).unwrap());
+        assert!(manager.list_revisions(&path).unwrap().is_empty());
+    }
+
+    #[test]
+    fn failed_add_leaves_nothing_behind() {
+        let manager = failing_manager();
+        manager.backend.fail_on(Failure::WriteAfter(10));
+        let mut cv = basic_cv_factory();
+        assert!(manager.add_cv(&mut cv).is_err());
+        // The CV can be added again once the storage works.
+        assert_eq!(None, cv.path);
+        manager.backend.fail_on(Failure::Nothing);
+        assert!(manager.list_cvs().unwrap().summaries.is_empty());
+        assert_eq!(0, manager.backend.path("/cvs").read_dir().unwrap().count());
+    }
 }
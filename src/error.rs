@@ -0,0 +1,193 @@
+// Responsibility: the errors shared by all the modules, so that the callers can tell apart
+// e.g. a missing file from a corrupt one and report them accordingly.
+use base::ValidationError;
+use schema::CURRENT_VERSION;
+use serde_json;
+use std::error;
+use std::fmt::{Display, Error, Formatter};
+use std::io;
+use std::path::PathBuf;
+use toml;
+
+pub type CVResult<T> = Result<T, CVError>;
+
+#[derive(Debug)]
+pub enum CVError {
+    // The file on the path could not be created, read, written or removed.
+    Io {
+        path: String,
+        source: io::Error,
+    },
+    // The CV could not be converted to or from JSON. The path is known when reading a stored CV.
+    Serialization {
+        path: Option<String>,
+        source: serde_json::Error,
+    },
+    Validation(ValidationError),
+    // The CV or the profile has been written in a newer version of the format than this build
+    // can read, see schema::CURRENT_VERSION.
+    UnsupportedVersion {
+        path: Option<String>,
+        version: u64,
+    },
+    // The config file of the application is not valid TOML or has unexpected contents.
+    Config {
+        path: String,
+        source: toml::de::Error,
+    },
+    // The CV could not be laid out or the document could not be written.
+    Rendering {
+        reason: String,
+        source: Option<Box<dyn error::Error>>,
+    },
+    // The font is broken or cannot be embedded into the document. There is no path for the
+    // fonts bundled into the binary.
+    Font {
+        path: Option<PathBuf>,
+        source: Box<dyn error::Error>,
+    },
+    // The custom font could not be used, the bundled one has taken its place. Only reported as
+    // a warning, the document is rendered anyway.
+    FontFallback(Box<CVError>),
+    // Adding a CV which has been stored already.
+    AlreadyStored,
+    // Updating or removing a CV which has never been stored.
+    NotStored,
+    // Reading the profile before it has been stored.
+    NoProfile,
+    // The CV has no revision with the id, see dao::Revision.
+    NoRevision(String),
+    // The command line arguments do not make up a valid command.
+    Usage(String),
+}
+
+impl CVError {
+    pub fn io(path: &str, source: io::Error) -> CVError {
+        CVError::Io {
+            path: path.to_string(),
+            source,
+        }
+    }
+
+    pub fn rendering(reason: &str) -> CVError {
+        CVError::Rendering {
+            reason: reason.to_string(),
+            source: None,
+        }
+    }
+}
+
+impl Display for CVError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        use self::CVError::*;
+        match self {
+            Io { path, source } => write!(f, "Could not access '{}': {}", path, source),
+            Serialization {
+                path: Some(path),
+                source,
+            } => write!(f, "The CV '{}' is corrupt: {}", path, source),
+            Serialization { path: None, source } => {
+                write!(f, "Could not serialize the CV: {}", source)
+            }
+            Validation(err) => write!(f, "Invalid data, {}", err),
+            UnsupportedVersion {
+                path: Some(path),
+                version,
+            } => write!(
+                f,
+                "The file '{}' is stored in the format version {}, only the versions up to {} are \
+                 supported. Please upgrade the application.",
+                path, version, CURRENT_VERSION
+            ),
+            UnsupportedVersion {
+                path: None,
+                version,
+            } => write!(
+                f,
+                "The data are stored in the format version {}, only the versions up to {} are \
+                 supported. Please upgrade the application.",
+                version, CURRENT_VERSION
+            ),
+            Config { path, source } => write!(f, "Invalid config file '{}': {}", path, source),
+            Rendering {
+                reason,
+                source: Some(source),
+            } => write!(f, "{}: {}", reason, source),
+            Rendering { reason, source: None } => write!(f, "{}", reason),
+            Font {
+                path: Some(path),
+                source,
+            } => write!(f, "Could not load the font {}: {}", path.display(), source),
+            Font { path: None, source } => write!(f, "Could not load the font: {}", source),
+            FontFallback(err) => write!(f, "{}, using the bundled one instead.", err),
+            AlreadyStored => write!(f, "Cannot add a CV which already has an ID."),
+            NotStored => write!(f, "The CV has no ID, it has not been stored yet."),
+            NoProfile => write!(f, "No profile has been stored yet."),
+            NoRevision(id) => write!(f, "The CV has no revision '{}'.", id),
+            Usage(message) => write!(f, "{} Run 'cvmaker help' to list the commands.", message),
+        }
+    }
+}
+
+impl error::Error for CVError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        use self::CVError::*;
+        match self {
+            Io { source, .. } => Some(source),
+            Serialization { source, .. } => Some(source),
+            Validation(err) => Some(err),
+            Config { source, .. } => Some(source),
+            Rendering {
+                source: Some(source),
+                ..
+            } => Some(source.as_ref()),
+            Font { source, .. } => Some(source.as_ref()),
+            FontFallback(err) => Some(err.as_ref()),
+            Rendering { source: None, .. }
+            | UnsupportedVersion { .. }
+            | AlreadyStored
+            | NotStored
+            | NoProfile
+            | NoRevision(_)
+            | Usage(_) => None,
+        }
+    }
+}
+
+impl From<ValidationError> for CVError {
+    fn from(err: ValidationError) -> CVError {
+        CVError::Validation(err)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::error::Error;
+
+    #[test]
+    fn io_error_keeps_path_and_source() {
+        let err = CVError::io("/foo.json", io::Error::new(io::ErrorKind::NotFound, "gone"));
+        assert_eq!(err.to_string(), "Could not access '/foo.json': gone");
+        assert!(err.source().is_some());
+    }
+
+    #[test]
+    fn corrupt_json_reported_with_path() {
+        let source = serde_json::from_str::<u32>("{").unwrap_err();
+        let err = CVError::Serialization {
+            path: Some("/foo.json".to_string()),
+            source,
+        };
+        assert!(err.to_string().starts_with("The CV '/foo.json' is corrupt: "));
+    }
+
+    #[test]
+    fn validation_error_converted() {
+        let err: CVError = ValidationError::NoContacts.into();
+        assert_eq!(
+            err.to_string(),
+            "Invalid data, contacts: at least one contact must be provided"
+        );
+    }
+}
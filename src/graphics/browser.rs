@@ -0,0 +1,231 @@
+// Responsibility: the main screen of the application. It lists the stored CVs and offers the
+// actions which can be taken on them (edit, clone, delete, render and going through the
+// history).
+use base::CV;
+use chrono::{DateTime, Local};
+use cursive::traits::{Boxable, Identifiable, Scrollable};
+use cursive::views::{Dialog, EditView, LinearLayout, SelectView, TextView};
+use cursive::Cursive;
+use dao::{CVDao, CVListing, CVManager, CVSummary};
+use error::{CVError, CVResult};
+use graphics::Graphics;
+use renderer::{default_file_name, render_pdf_to_file};
+use std::env;
+use std::time::SystemTime;
+
+static BROWSER_ID: &str = "cv_browser";
+static CLONE_LABEL_ID: &str = "clone_label";
+
+// The CVs are identified by their ids in the list.
+pub fn browser(data_dir: &str) -> Dialog {
+    let list: SelectView<String> = SelectView::new();
+    // The scroll view cannot handle empty content, hence the minimal size of the list.
+    let list = list.with_id(BROWSER_ID).min_size((1, 1)).scrollable();
+    Dialog::around(list.full_screen())
+        .title("Stored CVs")
+        .button("New", action(data_dir, |s, data_dir| {
+            Graphics::open_form(s, data_dir, None)
+        })).button("Edit", on_selected(data_dir, edit))
+        .button("Clone", on_selected(data_dir, confirm_clone))
+        .button("Delete", on_selected(data_dir, confirm_delete))
+        .button("Render", on_selected(data_dir, render))
+        .button("History", on_selected(data_dir, history))
+}
+
+// Reloads the list of the CVs from the storage.
+pub fn refresh(s: &mut Cursive, data_dir: &str) {
+    match CVDao::new(data_dir).and_then(|manager| manager.list_cvs()) {
+        Ok(CVListing {
+            summaries,
+            unreadable,
+        }) => {
+            s.call_on_id(BROWSER_ID, |list: &mut SelectView<String>| {
+                list.clear();
+                for summary in summaries {
+                    list.add_item(label(&summary), summary.id);
+                }
+            });
+            if !unreadable.is_empty() {
+                let reasons = unreadable
+                    .iter()
+                    .map(|(_, err)| err.to_string())
+                    .collect::<Vec<_>>();
+                show_error(
+                    s,
+                    &format!("Some CVs could not be read:\n{}", reasons.join("\n")),
+                );
+            }
+        }
+        Err(err) => show_error(s, &err.to_string()),
+    }
+}
+
+fn label(summary: &CVSummary) -> String {
+    let mut label = format!("{} {}", summary.name, summary.surname);
+    if let Some(ref cv_label) = summary.label {
+        label += &format!(" [{}]", cv_label);
+    }
+    if let Some(modified) = summary.modified {
+        label += &format!(" (modified {})", format_time(modified));
+    }
+    label
+}
+
+fn format_time(time: SystemTime) -> String {
+    let time: DateTime<Local> = time.into();
+    time.format("%Y-%m-%d %H:%M").to_string()
+}
+
+pub fn show_error(s: &mut Cursive, message: &str) {
+    s.add_layer(Dialog::info(format!("Error: {}", message)));
+}
+
+// Appends the problems which have not stopped the action to its message.
+pub fn with_warnings(message: String, warnings: &[CVError]) -> String {
+    warnings.iter().fold(message, |message, warning| {
+        format!("{}\nWarning: {}", message, warning)
+    })
+}
+
+// The callbacks have to own the data directory, the helpers below hand a copy of it over.
+fn action<F>(data_dir: &str, callback: F) -> impl Fn(&mut Cursive)
+where
+    F: Fn(&mut Cursive, &str),
+{
+    let data_dir = data_dir.to_string();
+    move |s| callback(s, &data_dir)
+}
+
+// Calls the callback with the CV selected in the list, if there is any.
+fn on_selected<F>(data_dir: &str, callback: F) -> impl Fn(&mut Cursive)
+where
+    F: Fn(&mut Cursive, &str, CV) -> CVResult<()>,
+{
+    action(data_dir, move |s, data_dir| {
+        let selection = s
+            .call_on_id(BROWSER_ID, |list: &mut SelectView<String>| list.selection())
+            .and_then(|selection| selection);
+        let id = match selection {
+            Some(id) => id,
+            None => return s.add_layer(Dialog::info("No CV selected.")),
+        };
+        let res = CVDao::new(data_dir)
+            .and_then(|manager| manager.read_cv(&id))
+            .and_then(|cv| callback(s, data_dir, cv));
+        if let Err(err) = res {
+            show_error(s, &err.to_string());
+        }
+    })
+}
+
+fn edit(s: &mut Cursive, data_dir: &str, cv: CV) -> CVResult<()> {
+    Graphics::open_form(s, data_dir, Some(cv));
+    Ok(())
+}
+
+// The copy is stored as a new CV, the original one is kept untouched. Unless the user gives the
+// copy a label, it keeps the label of the original.
+fn confirm_clone(s: &mut Cursive, data_dir: &str, cv: CV) -> CVResult<()> {
+    let path = cv.path.ok_or(CVError::NotStored)?;
+    let data_dir = data_dir.to_string();
+    let form = LinearLayout::vertical()
+        .child(TextView::new("Label of the copy (optional):"))
+        .child(EditView::new().with_id(CLONE_LABEL_ID).fixed_width(30));
+    s.add_layer(
+        Dialog::around(form)
+            .title("Clone CV")
+            .button("Clone", move |s| {
+                let label = s
+                    .call_on_id(CLONE_LABEL_ID, |v: &mut EditView| v.get_content())
+                    .unwrap();
+                s.pop_layer();
+                let label = Some(label.trim()).filter(|label| !label.is_empty());
+                match CVDao::new(&data_dir).and_then(|manager| manager.clone_cv(&path, label)) {
+                    Ok(_) => refresh(s, &data_dir),
+                    Err(err) => show_error(s, &err.to_string()),
+                }
+            }).dismiss_button("Cancel"),
+    );
+    Ok(())
+}
+
+fn confirm_delete(s: &mut Cursive, data_dir: &str, cv: CV) -> CVResult<()> {
+    let question = format!("Delete the CV of {} {}?", cv.basic.name, cv.basic.surname);
+    let data_dir = data_dir.to_string();
+    s.add_layer(
+        Dialog::text(question)
+            .title("Delete CV")
+            .button("Delete", move |s| {
+                s.pop_layer();
+                let mut cv = cv.clone();
+                match CVDao::new(&data_dir).and_then(|manager| manager.remove_cv(&mut cv)) {
+                    Ok(_) => refresh(s, &data_dir),
+                    Err(err) => show_error(s, &err.to_string()),
+                }
+            }).dismiss_button("Cancel"),
+    );
+    Ok(())
+}
+
+fn render(s: &mut Cursive, _: &str, cv: CV) -> CVResult<()> {
+    let pdf_path = env::temp_dir().join(default_file_name(&cv));
+    let warnings = render_pdf_to_file(&cv, &pdf_path)?;
+    let message = format!("CV rendered to {}.", pdf_path.display());
+    s.add_layer(Dialog::info(with_warnings(message, &warnings)));
+    Ok(())
+}
+
+// Lists the earlier revisions of the CV, choosing one shows what has changed since.
+fn history(s: &mut Cursive, data_dir: &str, cv: CV) -> CVResult<()> {
+    let path = cv.path.ok_or(CVError::NotStored)?;
+    let revisions = CVDao::new(data_dir)?.list_revisions(&path)?;
+    if revisions.is_empty() {
+        s.add_layer(Dialog::info("The CV has no earlier revisions."));
+        return Ok(());
+    }
+    let mut list = SelectView::new();
+    for revision in revisions {
+        list.add_item(format!("Replaced {}", format_time(revision.replaced)), revision.id);
+    }
+    let data_dir = data_dir.to_string();
+    list.set_on_submit(move |s, revision: &String| {
+        if let Err(err) = show_revision(s, &data_dir, &path, revision) {
+            show_error(s, &err.to_string());
+        }
+    });
+    s.add_layer(
+        Dialog::around(list.scrollable())
+            .title("Revisions")
+            .dismiss_button("Close"),
+    );
+    Ok(())
+}
+
+// The revision is compared with the current CV. Restoring it keeps the current content as
+// another revision.
+fn show_revision(s: &mut Cursive, data_dir: &str, path: &str, revision: &str) -> CVResult<()> {
+    let changes = CVDao::new(data_dir)?.diff_revisions(path, revision, None)?;
+    let text = if changes.is_empty() {
+        "The revision is the same as the current CV.".to_string()
+    } else {
+        let lines: Vec<String> = changes.iter().map(|change| change.to_string()).collect();
+        lines.join("\n")
+    };
+    let (data_dir, path, revision) = (data_dir.to_string(), path.to_string(), revision.to_string());
+    s.add_layer(
+        Dialog::around(TextView::new(text).scrollable())
+            .title("Changes since the revision")
+            .button("Restore", move |s| {
+                // Both the changes and the list of the revisions are outdated now.
+                s.pop_layer();
+                s.pop_layer();
+                let res = CVDao::new(&data_dir)
+                    .and_then(|manager| manager.restore_revision(&path, &revision));
+                match res {
+                    Ok(_) => refresh(s, &data_dir),
+                    Err(err) => show_error(s, &err.to_string()),
+                }
+            }).dismiss_button("Back"),
+    );
+    Ok(())
+}
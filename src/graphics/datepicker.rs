@@ -1,42 +1,131 @@
+use base::ValidationError;
 use chrono::{DateTime, Datelike, Local, NaiveDate};
 use cursive::traits::{Boxable, Finder, Identifiable};
 use cursive::view::ViewWrapper;
-use cursive::views::{IdView, LinearLayout, SelectView, TextContent, TextView};
+use cursive::views::{Checkbox, IdView, LinearLayout, SelectView, TextContent, TextView};
 use graphics::select_view_from_range;
+use std::cmp;
 use std::ops::Range;
 
-fn date_picker(label_text: &str, show_days: bool) -> LinearLayout {
+// The years are offered from the newest one down to 1900 unless the picker says otherwise.
+const FIRST_YEAR: i32 = 1900;
+// The publications may be much older, e.g. the reprinted classics.
+pub const FIRST_PUBLICATION_YEAR: i32 = 1450;
+// The certifications usually expire in a few years.
+const EXPIRY_YEARS_AHEAD: i32 = 30;
+
+fn newest_year() -> i32 {
     let dt: DateTime<Local> = Local::now();
-    let yr = dt.year() + 1;
+    dt.year()
+}
+
+// The first and the last year offered, both inclusive.
+type Years = (i32, i32);
+
+fn date_picker(label_text: &str, show_days: bool, (first, last): Years) -> LinearLayout {
     let mut res = LinearLayout::horizontal()
         .child(TextView::new_with_content(TextContent::new(label_text)).fixed_width(20))
-        .child(select_view_from_range((1900..yr).rev()).with_id("yr"))
+        .child(select_view_from_range((first..last + 1).rev()).with_id("yr"))
         .child(select_view_from_range::<u32, Range<u32>>(1..13).with_id("month"));
     if show_days {
-        res.add_child(select_view_from_range::<u32, Range<u32>>(1..32).with_id("day")); // TODO: this is wrong, Feb 31...?
+        // The days the month does not have are rejected once the date is retrieved.
+        res.add_child(select_view_from_range::<u32, Range<u32>>(1..32).with_id("day"));
     }
     res
 }
 
 pub trait DatePicker {
-    fn retrieve_date(&mut self) -> Option<NaiveDate>;
+    // Nothing is picked unless the pickers are filled, the days the month does not have are
+    // rejected.
+    fn retrieve_date(&mut self) -> Result<Option<NaiveDate>, ValidationError>;
 }
 
 pub struct DateView {
     view: LinearLayout,
+    // Named in the errors, e.g. "date of birth".
+    field: String,
+    years: Years,
+    // The date set in the pickers, its day is kept by the pickers without days.
+    stored: Option<NaiveDate>,
 }
 
 impl DateView {
-    pub fn new_full(id: &str) -> IdView<DateView> {
+    fn new(id: &str, show_days: bool, years: Years) -> DateView {
         DateView {
-            view: date_picker(id, true),
-        }.with_id(id)
+            view: date_picker(id, show_days, years),
+            field: id.to_lowercase(),
+            years,
+            stored: None,
+        }
+    }
+
+    pub fn new_full(id: &str) -> IdView<DateView> {
+        Self::new(id, true, (FIRST_YEAR, newest_year())).with_id(id)
+    }
+
+    // Full date picker offering the years since the given one.
+    pub fn new_full_since(id: &str, first_year: i32) -> IdView<DateView> {
+        Self::new(id, true, (first_year, newest_year())).with_id(id)
     }
 
     pub fn new_without_days(id: &str) -> IdView<DateView> {
-        DateView {
-            view: date_picker(id, false),
-        }.with_id(id)
+        Self::new(id, false, (FIRST_YEAR, newest_year())).with_id(id)
+    }
+
+    // Picker for the end of a time span which may not have ended yet.
+    pub fn new_open_ended(id: &str) -> IdView<DateView> {
+        let view = Self::new(id, false, (FIRST_YEAR, newest_year()));
+        view.with_open_end(" until now").with_id(id)
+    }
+
+    // Picker for the expiry of something which may not expire at all, the years to come are
+    // offered as well.
+    pub fn new_expiry(id: &str) -> IdView<DateView> {
+        let view = Self::new(id, true, (FIRST_YEAR, newest_year() + EXPIRY_YEARS_AHEAD));
+        view.with_open_end(" does not expire").with_id(id)
+    }
+
+    // The checkbox tells that there is no date to pick.
+    fn with_open_end(mut self, checkbox_text: &str) -> DateView {
+        self.view.add_child(Checkbox::new().with_id("open_end"));
+        self.view.add_child(TextView::new(checkbox_text));
+        self
+    }
+
+    pub fn is_open_ended(&mut self) -> bool {
+        self.view
+            .find_id("open_end", |c: &mut Checkbox| c.is_checked())
+            .unwrap_or(false)
+    }
+
+    pub fn set_open_ended(&mut self, open_ended: bool) {
+        self.view
+            .find_id("open_end", |c: &mut Checkbox| c.set_checked(open_ended));
+    }
+
+    // Selects the date in the pickers, the pickers without days remember the day instead. The
+    // year is added to the picker when missing, so that storing the form again keeps the date.
+    pub fn set_date(&mut self, date: NaiveDate) {
+        self.stored = Some(date);
+        let (first, last) = self.years;
+        let years = (cmp::min(first, date.year()), cmp::max(last, date.year()));
+        if years != self.years {
+            self.years = years;
+            self.view.find_id("yr", |s: &mut SelectView<i32>| {
+                s.clear();
+                for year in (years.0..years.1 + 1).rev() {
+                    s.add_item(year.to_string(), year);
+                }
+            });
+        }
+        let year_idx = (years.1 - date.year()) as usize;
+        self.view
+            .find_id("yr", |s: &mut SelectView<i32>| s.set_selection(year_idx));
+        self.view.find_id("month", |s: &mut SelectView<u32>| {
+            s.set_selection(date.month0() as usize)
+        });
+        self.view
+            .find_id("day", |s: &mut SelectView<u32>| s.set_selection(date.day0() as usize));
     }
 }
 
@@ -45,24 +134,111 @@ impl ViewWrapper for DateView {
 }
 
 impl DatePicker for DateView {
-    fn retrieve_date(&mut self) -> Option<NaiveDate> {
+    fn retrieve_date(&mut self) -> Result<Option<NaiveDate>, ValidationError> {
         let year = self
             .view
             .find_id("yr", |s: &mut SelectView<i32>| s.selection());
-        if let Some(Some(year)) = year {
-            let month = self
-                .view
-                .find_id("month", |s: &mut SelectView<u32>| s.selection());
-            if let Some(Some(month)) = month {
-                let day = self
-                    .view
-                    .find_id("day", |s: &mut SelectView<u32>| s.selection());
-                if let Some(Some(day)) = day {
-                    return Some(NaiveDate::from_ymd(*year, *month, *day));
-                }
-                return Some(NaiveDate::from_ymd(*year, *month, 1));
-            }
+        let month = self
+            .view
+            .find_id("month", |s: &mut SelectView<u32>| s.selection());
+        let (year, month) = match (year, month) {
+            (Some(Some(year)), Some(Some(month))) => (*year, *month),
+            _ => return Ok(None),
+        };
+        // The pickers without days stand for the first day of the month, unless the month is
+        // the one of the date set, whose day is kept.
+        let day = match self
+            .view
+            .find_id("day", |s: &mut SelectView<u32>| s.selection())
+        {
+            Some(Some(day)) => *day,
+            Some(None) => return Ok(None),
+            None => match self.stored {
+                Some(stored) if (stored.year(), stored.month()) == (year, month) => stored.day(),
+                _ => 1,
+            },
+        };
+        match NaiveDate::from_ymd_opt(year, month, day) {
+            Some(date) => Ok(Some(date)),
+            None => Err(ValidationError::InvalidDate {
+                field: self.field.clone(),
+                year,
+                month,
+                day,
+            }),
         }
-        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn pick_day(view: &mut DateView, day: u32) {
+        view.view
+            .find_id("day", |s: &mut SelectView<u32>| s.set_selection(day as usize - 1));
+    }
+
+    #[test]
+    fn existing_date_retrieved() {
+        let mut view = DateView::new_full("Published");
+        let date = NaiveDate::from_ymd(2004, 2, 29);
+        view.get_mut().set_date(date);
+        assert_eq!(Ok(Some(date)), view.get_mut().retrieve_date());
+    }
+
+    #[test]
+    fn missing_day_of_month_rejected() {
+        let mut view = DateView::new_full("Date of birth");
+        view.get_mut().set_date(NaiveDate::from_ymd(2001, 2, 1));
+        pick_day(&mut view.get_mut(), 31);
+        let err = view.get_mut().retrieve_date().unwrap_err();
+        assert_eq!("date of birth", err.field());
+        assert_eq!(
+            ValidationError::InvalidDate {
+                field: "date of birth".to_string(),
+                year: 2001,
+                month: 2,
+                day: 31,
+            },
+            err
+        );
+    }
+
+    #[test]
+    fn years_outside_picker_kept() {
+        let mut view = DateView::new_full("Published");
+        let old = NaiveDate::from_ymd(1866, 1, 15);
+        view.get_mut().set_date(old);
+        assert_eq!(Ok(Some(old)), view.get_mut().retrieve_date());
+        let mut view = DateView::new_expiry("Expires");
+        let far = NaiveDate::from_ymd(newest_year() + EXPIRY_YEARS_AHEAD + 5, 6, 30);
+        view.get_mut().set_date(far);
+        assert_eq!(Ok(Some(far)), view.get_mut().retrieve_date());
+    }
+
+    #[test]
+    fn month_picker_starts_on_first_day() {
+        let mut view = DateView::new_without_days("From");
+        view.get_mut().set_date(NaiveDate::from_ymd(2001, 4, 30));
+        view.get_mut()
+            .view
+            .find_id("month", |s: &mut SelectView<u32>| s.set_selection(1));
+        assert_eq!(
+            Ok(Some(NaiveDate::from_ymd(2001, 2, 1))),
+            view.get_mut().retrieve_date()
+        );
+    }
+
+    #[test]
+    fn month_picker_keeps_day_of_date_set() {
+        let mut view = DateView::new_without_days("From");
+        let date = NaiveDate::from_ymd(2015, 3, 17);
+        view.get_mut().set_date(date);
+        assert_eq!(Ok(Some(date)), view.get_mut().retrieve_date());
+        let mut view = DateView::new_open_ended("To");
+        let date = NaiveDate::from_ymd(2001, 4, 30);
+        view.get_mut().set_date(date);
+        assert_eq!(Ok(Some(date)), view.get_mut().retrieve_date());
     }
 }
@@ -1,9 +1,12 @@
-use self::datepicker::{DatePicker, DateView};
+use self::datepicker::{DatePicker, DateView, FIRST_PUBLICATION_YEAR};
 use base::contact_types;
 use base::LanguageProficiency;
 use base::TimeSpan;
 use base::{
-    BasicInfo, CVBuilder, Contact, Education, EmailAddress, Experience, Lang, Language, CV,
+    Address, BasicInfo, CVBuilder, Certification, Contact, CustomEntry, CustomField,
+    CustomSection, Education, EmailAddress, Experience, Lang, Language, LanguageCertificate,
+    LanguageLayout, LanguageSkills, Margins, PageSetup, PaperSize, Project, Publication, Template,
+    ValidationError, CV,
 };
 use chrono::NaiveDate;
 use cursive::align::HAlign;
@@ -12,20 +15,24 @@ use cursive::menu::MenuTree;
 use cursive::traits::*;
 use cursive::view::Selector;
 use cursive::views::{
-    BoxView, Button, Canvas, Dialog, EditView, IdView, LinearLayout, SelectView, TextContent,
+    BoxView, Button, Checkbox, Dialog, EditView, IdView, LinearLayout, SelectView, TextContent,
     TextView,
 };
 use cursive::Cursive;
 use dao::{CVDao, CVManager};
+use error::CVResult;
+use isocountry::CountryCode;
 use phonenumber::PhoneNumber;
-use renderer::render_pdf;
-use std::error::Error;
+use renderer::{default_file_name, render_pdf_to_file};
+use std::cmp;
+use std::env;
 use std::fmt::Display;
 use std::rc::Rc;
 use std::str::FromStr;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use url::Url;
 
+mod browser;
 mod datepicker;
 
 fn select_view_from_range<S: Display + 'static, T: Iterator<Item = S>>(rng: T) -> SelectView<S> {
@@ -34,26 +41,77 @@ fn select_view_from_range<S: Display + 'static, T: Iterator<Item = S>>(rng: T) -
     sel_view.popup()
 }
 
+fn unique_id(prefix: &str) -> String {
+    format!("{}_{}", prefix, UNIQUE_ID_COUNT.fetch_add(1, Ordering::SeqCst))
+}
+
 static CONTACTS_ID: &'static str = "contacts";
 static CONTACT_CHILD_ID: &'static str = "contact_child";
+static ADDRESSES_ID: &str = "addresses";
+static ADDRESS_CHILD_ID: &str = "address_child";
+static ADDRESS_LINE_ID: &str = "Address line";
+// The number of the address lines offered in the form, the stored addresses may have more.
+const ADDRESS_LINES: usize = 2;
 static EXP_ID: &'static str = "experience";
 static EXP_CHILD_ID: &'static str = "experience_child";
-//TODO inspect whether this will be needed (the same for FORM_ROOT_ID)
-#[allow(dead_code)]
 static LANGS_ID: &'static str = "languages";
 static LANG_CHILD_ID: &'static str = "language_child";
+static LANG_SKILLS_ID: &str = "language_skills";
+static LANG_CERT_CHILD_ID: &str = "language_certificate_child";
 static EDU_ID: &'static str = "education";
 static EDU_CHILD_ID: &'static str = "education_child";
+static PROJECTS_ID: &str = "projects";
+static PROJECT_CHILD_ID: &str = "project_child";
+static PUBS_ID: &str = "publications";
+static PUB_CHILD_ID: &str = "publication_child";
+static CERTS_ID: &str = "certifications";
+static CERT_CHILD_ID: &str = "certification_child";
+static CUSTOM_FIELDS_ID: &str = "custom fields";
+static CUSTOM_FIELD_CHILD_ID: &str = "custom_field_child";
+static CUSTOM_SECTIONS_ID: &str = "custom sections";
+static CUSTOM_SECTION_CHILD_ID: &str = "custom_section_child";
+static CUSTOM_ENTRY_CHILD_ID: &str = "custom_entry_child";
+static CUSTOM_ENTRY_DATED_ID: &str = "custom_entry_dated";
+static LANGUAGE_RESULTS_ID: &str = "language_results";
+// The views which can appear any number of times in the form (e.g. the entries of every custom
+// section, the language pickers) are told apart by the number appended to their ids.
+static UNIQUE_ID_COUNT: AtomicUsize = AtomicUsize::new(0);
+//TODO inspect whether this will be needed
 #[allow(dead_code)]
 static FORM_ROOT_ID: &'static str = "form_root";
+static LABEL_ID: &str = "Label";
+static TEMPLATE_ID: &str = "template";
+static LANGUAGE_LAYOUT_ID: &str = "language layout";
+static PAPER_SIZE_ID: &str = "paper_size";
+static CUSTOM_PAPER_ID: &str = "Custom";
+static PAPER_WIDTH_ID: &str = "Paper width (mm)";
+static PAPER_HEIGHT_ID: &str = "Paper height (mm)";
+static MARGIN_IDS: [&str; 4] = [
+    "Top margin (mm)",
+    "Bottom margin (mm)",
+    "Left margin (mm)",
+    "Right margin (mm)",
+];
 
 pub struct Graphics {
     engine: Cursive,
+    // Directory the CVs are stored in.
+    data_dir: String,
+    // Messages shown to the user once the UI starts.
+    notices: Vec<String>,
 }
 
 impl Graphics {
-    pub fn new(engine: Cursive) -> Graphics {
-        Graphics { engine }
+    pub fn new(engine: Cursive, data_dir: &str) -> Graphics {
+        Graphics {
+            engine,
+            data_dir: data_dir.to_string(),
+            notices: vec![],
+        }
+    }
+
+    pub fn notify(&mut self, message: &str) {
+        self.notices.push(message.to_string());
     }
 
     fn setup_looks(&mut self) {
@@ -64,20 +122,23 @@ impl Graphics {
         }
     }
 
-    pub fn run(&mut self) -> Result<(), Box<Error>> {
-        self.init()?;
+    pub fn run(&mut self) -> CVResult<()> {
+        self.init();
         self.engine.run();
         Ok(())
     }
 
-    fn init(&mut self) -> Result<(), Box<Error>> {
+    fn init(&mut self) {
         self.setup_looks();
         self.add_menu();
-        self.engine.add_layer(Canvas::new(()));
-        self.add_form();
+        self.engine
+            .add_fullscreen_layer(browser::browser(&self.data_dir));
+        browser::refresh(&mut self.engine, &self.data_dir);
+        for notice in self.notices.drain(..) {
+            self.engine.add_layer(Dialog::info(notice));
+        }
         self.engine
             .add_global_callback(Key::Esc, |s| s.select_menubar());
-        Ok(())
     }
 
     // Creates a form row containing description on the left and an editable field on the right.
@@ -104,91 +165,504 @@ impl Graphics {
         Self::form_row(label_text, 20)
     }
 
+    // The addresses do not fit on a single row, they are entered in a sub-form of their own.
+    fn single_row_contact_types() -> Vec<&'static str> {
+        contact_types()
+            .into_iter()
+            .filter(|&item| item != "address")
+            .collect()
+    }
+
     fn contact_select_view() -> SelectView<String> {
         let mut sel_view: SelectView<String> = SelectView::new().h_align(HAlign::Center);
-        contact_types()
+        Self::single_row_contact_types()
             .iter()
             .for_each(|&item| sel_view.add_item(item, item.to_string()));
         sel_view.popup()
     }
 
+    // The value of the contact as it is entered in the single row form.
+    fn contact_value(contact: &Contact) -> Option<(&'static str, String)> {
+        match contact {
+            Contact::Email(address) => Some(("email", address.as_str().to_string())),
+            Contact::Website(url) => Some(("website", url.to_string())),
+            Contact::Phone(number) => Some(("phone", number.to_string())),
+            Contact::Address(_) => None,
+        }
+    }
+
+    fn contact_row_view(contact: Option<&Contact>) -> IdView<LinearLayout> {
+        let mut sel_view = Self::contact_select_view();
+        let mut edit_view = EditView::new();
+        if let Some((contact_type, value)) = contact.and_then(Self::contact_value) {
+            let types = Self::single_row_contact_types();
+            if let Some(idx) = types.iter().position(|&t| t == contact_type) {
+                sel_view.set_selection(idx);
+            }
+            edit_view.set_content(value);
+        }
+        LinearLayout::horizontal()
+            .child(sel_view)
+            .child(edit_view.fixed_width(20))
+            .with_id(CONTACT_CHILD_ID)
+    }
+
     fn contact_row(s: &mut Cursive) {
         s.call_on_id(CONTACTS_ID, |view: &mut LinearLayout| {
-            view.add_child(
-                LinearLayout::horizontal()
-                    .child(Self::contact_select_view())
-                    .child(EditView::new().fixed_width(20))
-                    .with_id(CONTACT_CHILD_ID),
-            )
+            view.add_child(Self::contact_row_view(None))
         });
     }
 
+    // The free-form lines of the address come first, the country is entered as the ISO code.
+    fn address_row_view(address: Option<&Address>) -> IdView<LinearLayout> {
+        let lines = address.map_or(vec![], |address| address.lines.clone());
+        let mut line_rows = LinearLayout::vertical();
+        for idx in 0..cmp::max(ADDRESS_LINES, lines.len()) {
+            let mut line_row = Self::form_row_default_col_size(ADDRESS_LINE_ID);
+            if let Some(line) = lines.get(idx) {
+                Self::set_row_content(&mut line_row, ADDRESS_LINE_ID, line);
+            }
+            line_rows.add_child(line_row);
+        }
+        let mut row = LinearLayout::vertical()
+            .child(line_rows)
+            .child(Self::form_row_default_col_size("City"))
+            .child(Self::form_row_default_col_size("Region"))
+            .child(Self::form_row_default_col_size("Postcode"))
+            .child(Self::form_row_default_col_size("Country code"));
+        if let Some(address) = address {
+            Self::set_row_content(&mut row, "City", &address.city);
+            if let Some(ref region) = address.region {
+                Self::set_row_content(&mut row, "Region", region);
+            }
+            if let Some(ref postal_code) = address.postal_code {
+                Self::set_row_content(&mut row, "Postcode", postal_code);
+            }
+            Self::set_row_content(&mut row, "Country code", address.country.alpha2());
+        }
+        row.with_id(ADDRESS_CHILD_ID)
+    }
+
+    fn address_row(s: &mut Cursive) {
+        s.call_on_id(ADDRESSES_ID, |view: &mut LinearLayout| {
+            view.add_child(Self::address_row_view(None))
+        });
+    }
+
+    fn set_row_content(row: &mut LinearLayout, label: &str, content: &str) {
+        row.find_id(label, |v: &mut BoxView<EditView>| {
+            v.get_inner_mut().set_content(content.to_string())
+        });
+    }
+
+    // Fills the "From" and "To" pickers of the row, an ongoing span is marked "until now".
+    fn set_row_span(row: &mut LinearLayout, span: &TimeSpan) {
+        row.find_id("From", |d: &mut DateView| d.set_date(span.from()));
+        row.find_id("To", |d: &mut DateView| match span.to() {
+            Some(to) => d.set_date(to),
+            None => d.set_open_ended(true),
+        });
+    }
+
+    fn experience_row_view(experience: Option<&Experience>) -> IdView<LinearLayout> {
+        let mut row = LinearLayout::vertical()
+            .child(DateView::new_without_days("From"))
+            .child(DateView::new_open_ended("To"))
+            .child(Self::form_row_default_col_size("Employer"))
+            .child(Self::form_row_default_col_size("Job name"))
+            .child(Self::form_row_default_col_size("Description"));
+        if let Some(experience) = experience {
+            Self::set_row_span(&mut row, &experience.span);
+            Self::set_row_content(&mut row, "Employer", &experience.employer);
+            Self::set_row_content(&mut row, "Job name", &experience.job_name);
+            Self::set_row_content(&mut row, "Description", &experience.description);
+        }
+        row.with_id(EXP_CHILD_ID)
+    }
+
     fn experience_row(s: &mut Cursive) {
         s.call_on_id(EXP_ID, |view: &mut LinearLayout| {
-            view.add_child(
+            view.add_child(Self::experience_row_view(None))
+        });
+    }
+
+    fn language_row_view(lang: Option<&Lang>) -> IdView<LinearLayout> {
+        let (button_id, code_id) = (unique_id("language"), unique_id("language_code"));
+        let (label, code) = match lang {
+            Some(lang) => (lang.language.to_string(), lang.language.code().to_string()),
+            None => ("Choose...".to_string(), String::new()),
+        };
+        let language = {
+            let (button_id, code_id) = (button_id.clone(), code_id.clone());
+            Button::new(label, move |s| {
+                Self::pick_language(s, button_id.clone(), code_id.clone())
+            })
+        };
+        let skills = lang.and_then(|lang| lang.skills.as_ref());
+        let mut row = LinearLayout::vertical()
+            .child(
+                LinearLayout::horizontal()
+                    .child(
+                        TextView::new_with_content(TextContent::new("Language name"))
+                            .fixed_width(20),
+                    ).child(language.with_id(button_id))
+                    .child(TextView::new(code).with_id(code_id)),
+            ).child(Self::proficiency_row(
+                "Proficiency",
+                lang.map(|lang| &lang.proficiency),
+            )).child(Self::form_row_default_col_size("Additional notes"))
+            .child(
+                LinearLayout::horizontal()
+                    .child(Checkbox::new().with_id(LANG_SKILLS_ID))
+                    .child(TextView::new(" assess the single skills")),
+            );
+        for (i, name) in LanguageSkills::NAMES.iter().enumerate() {
+            let level = skills.map(|skills| skills.levels()[i]);
+            row.add_child(Self::proficiency_row(name, level));
+        }
+        let certificates_id = unique_id("language_certificates");
+        let mut certificates = LinearLayout::vertical();
+        if let Some(lang) = lang {
+            Self::set_row_content(&mut row, "Additional notes", &lang.notes);
+            if skills.is_some() {
+                row.find_id(LANG_SKILLS_ID, |c: &mut Checkbox| c.check());
+            }
+            Self::rows(&lang.certificates, Self::language_certificate_row_view)
+                .into_iter()
+                .for_each(|row| certificates.add_child(row));
+        }
+        let add_certificate = {
+            let certificates_id = certificates_id.clone();
+            Button::new("Add certificate", move |s| {
+                s.call_on_id(&certificates_id, |view: &mut LinearLayout| {
+                    view.add_child(Self::language_certificate_row_view(None))
+                });
+            })
+        };
+        row.add_child(certificates.with_id(certificates_id));
+        row.add_child(LinearLayout::horizontal().child(add_certificate));
+        row.with_id(LANG_CHILD_ID)
+    }
+
+    fn proficiency_row(label: &str, proficiency: Option<&LanguageProficiency>) -> LinearLayout {
+        let mut select = select_view_from_range(LanguageProficiency::iterator());
+        if let Some(proficiency) = proficiency {
+            if let Some(idx) = LanguageProficiency::iterator().position(|p| p == proficiency) {
+                select.set_selection(idx);
+            }
+        }
+        LinearLayout::horizontal()
+            .child(TextView::new_with_content(TextContent::new(label)).fixed_width(20))
+            .child(select)
+    }
+
+    fn language_certificate_row_view(
+        certificate: Option<&LanguageCertificate>,
+    ) -> IdView<LinearLayout> {
+        let mut row = LinearLayout::vertical()
+            .child(Self::form_row_default_col_size("Certificate"))
+            .child(Self::form_row_default_col_size("Score"));
+        if let Some(certificate) = certificate {
+            Self::set_row_content(&mut row, "Certificate", &certificate.name);
+            Self::set_row_content(&mut row, "Score", &certificate.score);
+        }
+        row.with_id(LANG_CERT_CHILD_ID)
+    }
+
+    // Lets the user search the languages by the name, the endonym or the code. The chosen one is
+    // shown on the button of the row, the code next to it is what gets collected.
+    fn pick_language(s: &mut Cursive, button_id: String, code_id: String) {
+        let results = SelectView::<Language>::new().on_submit(move |s, language: &Language| {
+            s.pop_layer();
+            s.call_on_id(&button_id, |button: &mut Button| {
+                button.set_label(language.to_string())
+            });
+            s.call_on_id(&code_id, |view: &mut TextView| {
+                view.set_content(language.code())
+            });
+        });
+        let search = EditView::new().on_edit(|s, query, _| Self::show_languages(s, query));
+        s.add_layer(
+            Dialog::around(
                 LinearLayout::vertical()
-                    .child(DateView::new_without_days("From"))
-                    .child(DateView::new_without_days("To"))
-                    .child(Self::form_row_default_col_size("Employer"))
-                    .child(Self::form_row_default_col_size("Job name"))
-                    .child(Self::form_row_default_col_size("Description"))
-                    .with_id(EXP_CHILD_ID),
-            )
+                    .child(search.fixed_width(40))
+                    .child(results.with_id(LANGUAGE_RESULTS_ID).scrollable().fixed_height(10)),
+            ).title("Search the language")
+            .dismiss_button("Cancel"),
+        );
+        Self::show_languages(s, "");
+    }
+
+    fn show_languages(s: &mut Cursive, query: &str) {
+        s.call_on_id(LANGUAGE_RESULTS_ID, |view: &mut SelectView<Language>| {
+            view.clear();
+            for language in Language::search(query) {
+                let label = match language.endonym() {
+                    Some(endonym) => format!("{} ({}) [{}]", language, endonym, language.code()),
+                    None => format!("{} [{}]", language, language.code()),
+                };
+                view.add_item(label, language);
+            }
         });
     }
 
-    //TODO : if user enters the "Other" option, let him fill in the "other" language
     fn language_row(s: &mut Cursive) {
         s.call_on_id(LANGS_ID, |view: &mut LinearLayout| {
-            view.add_child(
-                LinearLayout::vertical()
-                    .child(
-                        LinearLayout::horizontal()
-                            .child(
-                                TextView::new_with_content(TextContent::new("Language name"))
-                                    .fixed_width(20),
-                            ).child(select_view_from_range(Language::iterator())),
-                    ).child(
-                        LinearLayout::horizontal()
-                            .child(
-                                TextView::new_with_content(TextContent::new("Proficiency"))
-                                    .fixed_width(20),
-                            ).child(select_view_from_range(LanguageProficiency::iterator())),
-                    ).child(Self::form_row_default_col_size("Additional notes"))
-                    .with_id(LANG_CHILD_ID),
-            )
+            view.add_child(Self::language_row_view(None))
         });
     }
 
+    fn education_row_view(education: Option<&Education>) -> IdView<LinearLayout> {
+        let mut row = LinearLayout::vertical()
+            .child(DateView::new_without_days("From"))
+            .child(DateView::new_open_ended("To"))
+            .child(Self::form_row_default_col_size("University"))
+            .child(Self::form_row_default_col_size("Degree"))
+            .child(Self::form_row_default_col_size("Field of study"));
+        if let Some(education) = education {
+            Self::set_row_span(&mut row, &education.span);
+            Self::set_row_content(&mut row, "University", &education.uni_name);
+            Self::set_row_content(&mut row, "Degree", &education.degree);
+            Self::set_row_content(&mut row, "Field of study", &education.field_of_study);
+        }
+        row.with_id(EDU_CHILD_ID)
+    }
+
     fn education_row(s: &mut Cursive) {
         s.call_on_id(EDU_ID, |view: &mut LinearLayout| {
-            view.add_child(
-                LinearLayout::vertical()
-                    .child(DateView::new_without_days("From"))
-                    .child(DateView::new_without_days("To"))
-                    .child(Self::form_row_default_col_size("University"))
-                    .child(Self::form_row_default_col_size("Degree"))
-                    .child(Self::form_row_default_col_size("Field of study"))
-                    .with_id(EDU_CHILD_ID),
-            )
+            view.add_child(Self::education_row_view(None))
         });
     }
 
-    fn expandable_linear_layout_contacts(event_fun: &'static Fn(&mut Cursive)) -> LinearLayout {
+    fn project_row_view(project: Option<&Project>) -> IdView<LinearLayout> {
+        let mut row = LinearLayout::vertical()
+            .child(DateView::new_without_days("From"))
+            .child(DateView::new_open_ended("To"))
+            .child(Self::form_row_default_col_size("Project"))
+            .child(Self::form_row_default_col_size("Project URL"))
+            .child(Self::form_row_default_col_size("Role"))
+            .child(Self::form_row_default_col_size("Technologies"));
+        if let Some(project) = project {
+            Self::set_row_span(&mut row, &project.span);
+            Self::set_row_content(&mut row, "Project", &project.name);
+            if let Some(ref url) = project.url {
+                Self::set_row_content(&mut row, "Project URL", url.as_str());
+            }
+            Self::set_row_content(&mut row, "Role", &project.role);
+            Self::set_row_content(&mut row, "Technologies", &project.technologies.join(", "));
+        }
+        row.with_id(PROJECT_CHILD_ID)
+    }
+
+    fn project_row(s: &mut Cursive) {
+        s.call_on_id(PROJECTS_ID, |view: &mut LinearLayout| {
+            view.add_child(Self::project_row_view(None))
+        });
+    }
+
+    fn publication_row_view(publication: Option<&Publication>) -> IdView<LinearLayout> {
+        let mut row = LinearLayout::vertical()
+            .child(Self::form_row_default_col_size("Title"))
+            .child(Self::form_row_default_col_size("Venue"))
+            .child(DateView::new_full_since("Published", FIRST_PUBLICATION_YEAR))
+            .child(Self::form_row_default_col_size("DOI"))
+            .child(Self::form_row_default_col_size("Publication URL"))
+            .child(Self::form_row_default_col_size("Co-authors"));
+        if let Some(publication) = publication {
+            Self::set_row_content(&mut row, "Title", &publication.title);
+            Self::set_row_content(&mut row, "Venue", &publication.venue);
+            row.find_id("Published", |d: &mut DateView| d.set_date(publication.date));
+            if let Some(ref doi) = publication.doi {
+                Self::set_row_content(&mut row, "DOI", doi);
+            }
+            if let Some(ref url) = publication.url {
+                Self::set_row_content(&mut row, "Publication URL", url.as_str());
+            }
+            Self::set_row_content(&mut row, "Co-authors", &publication.co_authors.join(", "));
+        }
+        row.with_id(PUB_CHILD_ID)
+    }
+
+    fn publication_row(s: &mut Cursive) {
+        s.call_on_id(PUBS_ID, |view: &mut LinearLayout| {
+            view.add_child(Self::publication_row_view(None))
+        });
+    }
+
+    fn certification_row_view(certification: Option<&Certification>) -> IdView<LinearLayout> {
+        let mut row = LinearLayout::vertical()
+            .child(Self::form_row_default_col_size("Certification"))
+            .child(Self::form_row_default_col_size("Issuer"))
+            .child(DateView::new_full("Obtained"))
+            .child(DateView::new_expiry("Expires"))
+            .child(Self::form_row_default_col_size("Credential ID"));
+        if let Some(certification) = certification {
+            Self::set_row_content(&mut row, "Certification", &certification.name);
+            Self::set_row_content(&mut row, "Issuer", &certification.issuer);
+            row.find_id("Obtained", |d: &mut DateView| d.set_date(certification.obtained));
+            row.find_id("Expires", |d: &mut DateView| match certification.expires {
+                Some(expires) => d.set_date(expires),
+                None => d.set_open_ended(true),
+            });
+            if let Some(ref id) = certification.credential_id {
+                Self::set_row_content(&mut row, "Credential ID", id);
+            }
+        }
+        row.with_id(CERT_CHILD_ID)
+    }
+
+    fn certification_row(s: &mut Cursive) {
+        s.call_on_id(CERTS_ID, |view: &mut LinearLayout| {
+            view.add_child(Self::certification_row_view(None))
+        });
+    }
+
+    fn custom_field_row_view(field: Option<&CustomField>) -> IdView<LinearLayout> {
+        let mut name_view = EditView::new();
+        let mut value_view = EditView::new();
+        if let Some(field) = field {
+            name_view.set_content(field.name.clone());
+            value_view.set_content(field.value.clone());
+        }
+        LinearLayout::horizontal()
+            .child(name_view.fixed_width(20))
+            .child(value_view.fixed_width(20))
+            .with_id(CUSTOM_FIELD_CHILD_ID)
+    }
+
+    fn custom_field_row(s: &mut Cursive) {
+        s.call_on_id(CUSTOM_FIELDS_ID, |view: &mut LinearLayout| {
+            view.add_child(Self::custom_field_row_view(None))
+        });
+    }
+
+    // The span of the entry is optional, the pickers are used only when the entry is dated.
+    fn custom_entry_row_view(entry: Option<&CustomEntry>) -> IdView<LinearLayout> {
+        let mut row = LinearLayout::vertical()
+            .child(
+                LinearLayout::horizontal()
+                    .child(Checkbox::new().with_id(CUSTOM_ENTRY_DATED_ID))
+                    .child(TextView::new(" dated")),
+            ).child(DateView::new_without_days("From"))
+            .child(DateView::new_open_ended("To"))
+            .child(Self::form_row_default_col_size("Heading"))
+            .child(Self::form_row_default_col_size("Subheading"))
+            .child(Self::form_row_default_col_size("Body"));
+        if let Some(entry) = entry {
+            if let Some(ref span) = entry.span {
+                row.find_id(CUSTOM_ENTRY_DATED_ID, |c: &mut Checkbox| c.check());
+                Self::set_row_span(&mut row, span);
+            }
+            Self::set_row_content(&mut row, "Heading", &entry.heading);
+            Self::set_row_content(&mut row, "Subheading", &entry.subheading);
+            Self::set_row_content(&mut row, "Body", &entry.body);
+        }
+        row.with_id(CUSTOM_ENTRY_CHILD_ID)
+    }
+
+    // The title of the section, its entries and the button adding another entry to them.
+    fn custom_section_row_view(section: Option<&CustomSection>) -> IdView<LinearLayout> {
+        let entries_id = unique_id("custom_entries");
+        let mut title = Self::form_row_default_col_size("Section title");
+        let mut entries = LinearLayout::vertical();
+        if let Some(section) = section {
+            Self::set_row_content(&mut title, "Section title", &section.title);
+            Self::rows(&section.entries, Self::custom_entry_row_view)
+                .into_iter()
+                .for_each(|row| entries.add_child(row));
+        }
+        let add_entry = {
+            let entries_id = entries_id.clone();
+            Button::new("Add entry", move |s| {
+                s.call_on_id(&entries_id, |view: &mut LinearLayout| {
+                    view.add_child(Self::custom_entry_row_view(None))
+                });
+            })
+        };
+        LinearLayout::vertical()
+            .child(title)
+            .child(entries.with_id(entries_id))
+            .child(LinearLayout::horizontal().child(add_entry))
+            .with_id(CUSTOM_SECTION_CHILD_ID)
+    }
+
+    fn custom_section_row(s: &mut Cursive) {
+        s.call_on_id(CUSTOM_SECTIONS_ID, |view: &mut LinearLayout| {
+            view.add_child(Self::custom_section_row_view(None))
+        });
+    }
+
+    fn expandable_linear_layout_contacts(
+        rows: Vec<IdView<LinearLayout>>,
+        address_rows: Vec<IdView<LinearLayout>>,
+        event_fun: &'static dyn Fn(&mut Cursive),
+    ) -> LinearLayout {
+        let mut contacts = LinearLayout::vertical();
+        rows.into_iter().for_each(|row| contacts.add_child(row));
+        let mut addresses = LinearLayout::vertical();
+        address_rows
+            .into_iter()
+            .for_each(|row| addresses.add_child(row));
         LinearLayout::vertical()
             .child(
                 LinearLayout::horizontal().child(
                     TextView::new_with_content(TextContent::new("Contacts")).fixed_width(20),
                 ),
+            ).child(contacts.with_id(CONTACTS_ID))
+            .child(LinearLayout::horizontal().child(Button::new("Add another", event_fun)))
+            .child(addresses.with_id(ADDRESSES_ID))
+            .child(LinearLayout::horizontal().child(Button::new("Add address", Self::address_row)))
+    }
+
+    // Template selection, paper size selection (the named sizes and a custom one, whose
+    // dimensions are taken from the width and height rows) and the page margins.
+    fn page_setup_layout() -> LinearLayout {
+        let mut sizes: SelectView<String> = SelectView::new().h_align(HAlign::Center);
+        PaperSize::iterator().for_each(|size| sizes.add_item(size.to_string(), size.to_string()));
+        sizes.add_item(CUSTOM_PAPER_ID, CUSTOM_PAPER_ID.to_string());
+        let mut layout = LinearLayout::vertical()
+            .child(
+                LinearLayout::horizontal().child(
+                    TextView::new_with_content(TextContent::new("Page setup")).fixed_width(20),
+                ),
             ).child(
-                LinearLayout::vertical()
+                LinearLayout::horizontal()
+                    .child(TextView::new_with_content(TextContent::new("Template")).fixed_width(20))
+                    .child(
+                        select_view_from_range(Template::iterator().cloned()).with_id(TEMPLATE_ID),
+                    ),
+            ).child(
+                LinearLayout::horizontal()
                     .child(
-                        LinearLayout::horizontal()
-                            .child(Self::contact_select_view())
-                            .child(EditView::new().fixed_width(20))
-                            .with_id(CONTACT_CHILD_ID),
-                    ).with_id(CONTACTS_ID),
-            ).child(LinearLayout::horizontal().child(Button::new("Add another", event_fun)))
+                        TextView::new_with_content(TextContent::new("Language layout"))
+                            .fixed_width(20),
+                    ).child(
+                        select_view_from_range(LanguageLayout::iterator().cloned())
+                            .with_id(LANGUAGE_LAYOUT_ID),
+                    ),
+            ).child(
+                LinearLayout::horizontal()
+                    .child(
+                        TextView::new_with_content(TextContent::new("Paper size"))
+                            .fixed_width(20),
+                    ).child(sizes.popup().with_id(PAPER_SIZE_ID)),
+            ).child(Self::form_row_default_col_size(PAPER_WIDTH_ID))
+            .child(Self::form_row_default_col_size(PAPER_HEIGHT_ID));
+        let default_margins = Margins::default();
+        let defaults = [
+            default_margins.top,
+            default_margins.bottom,
+            default_margins.left,
+            default_margins.right,
+        ];
+        for (label, default) in MARGIN_IDS.iter().zip(defaults.iter()) {
+            let mut row = Self::form_row_default_col_size(label);
+            row.find_id(label, |s: &mut BoxView<EditView>| {
+                s.get_inner_mut().set_content(default.to_string())
+            });
+            layout.add_child(row);
+        }
+        layout
     }
 
     fn first_uppercase(s: &str) -> String {
@@ -201,60 +675,166 @@ impl Graphics {
 
     fn expandable_linear_layout<'a>(
         label: &'a str,
-        event_fun: &'static Fn(&mut Cursive),
+        rows: Vec<IdView<LinearLayout>>,
+        event_fun: &'static dyn Fn(&mut Cursive),
     ) -> LinearLayout {
+        let mut children = LinearLayout::vertical();
+        rows.into_iter().for_each(|row| children.add_child(row));
         LinearLayout::vertical()
             .child(
                 LinearLayout::horizontal().child(
                     TextView::new_with_content(TextContent::new(Self::first_uppercase(label)))
                         .fixed_width(20),
                 ),
-            ).child(LinearLayout::vertical().with_id(label))
+            ).child(children.with_id(label))
             .child(LinearLayout::horizontal().child(Button::new("Add another", event_fun)))
     }
 
-    fn add_form(&mut self) {
+    // One row for every entry, filled with the entry.
+    fn rows<T>(
+        entries: &[T],
+        row_view: fn(Option<&T>) -> IdView<LinearLayout>,
+    ) -> Vec<IdView<LinearLayout>> {
+        entries.iter().map(|entry| row_view(Some(entry))).collect()
+    }
+
+    // Opens the form for a new CV or, when a stored CV is given, the form filled with the CV.
+    fn open_form(s: &mut Cursive, data_dir: &str, stored: Option<CV>) {
+        let data_dir = data_dir.to_string();
+        let path = stored.as_ref().and_then(|cv| cv.path.clone());
+        // The form does not show the link to the profile, the stored one is kept.
+        let link = stored.as_ref().and_then(|cv| cv.profile.clone());
+        let (title, button) = match stored {
+            Some(_) => ("Edit CV", "Save changes"),
+            None => ("New CV", "Create new CV"),
+        };
+        // The rows are filled with the entries of the stored CV, a new CV has none.
+        let entries = stored.clone().unwrap_or_default();
+        let (single_row, addresses): (Vec<Contact>, Vec<Contact>) = entries
+            .basic
+            .contacts
+            .iter()
+            .cloned()
+            .partition(|contact| Self::contact_value(contact).is_some());
+        let addresses: Vec<Address> = addresses
+            .into_iter()
+            .filter_map(|contact| match contact {
+                Contact::Address(address) => Some(address),
+                _ => None,
+            }).collect();
+        let mut contact_rows = Self::rows(&single_row, Self::contact_row_view);
+        if contact_rows.is_empty() && addresses.is_empty() {
+            contact_rows.push(Self::contact_row_view(None));
+        }
         let form = LinearLayout::vertical()
             .child(Self::form_row_default_col_size("Name"))
             .child(Self::form_row_default_col_size("Surname"))
+            .child(Self::form_row_default_col_size(LABEL_ID))
             .child(DateView::new_full("Date of birth"))
-            .child(Self::expandable_linear_layout_contacts(&Self::contact_row))
-            .child(Self::expandable_linear_layout(
+            .child(Self::expandable_linear_layout_contacts(
+                contact_rows,
+                Self::rows(&addresses, Self::address_row_view),
+                &Self::contact_row,
+            )).child(Self::expandable_linear_layout(
+                CUSTOM_FIELDS_ID,
+                Self::rows(&entries.basic.custom_fields, Self::custom_field_row_view),
+                &Self::custom_field_row,
+            )).child(Self::expandable_linear_layout(
                 LANGS_ID,
+                Self::rows(&entries.languages, Self::language_row_view),
                 &Self::language_row,
-            )).child(Self::expandable_linear_layout(EDU_ID, &Self::education_row))
-            .child(Self::expandable_linear_layout(
+            )).child(Self::expandable_linear_layout(
+                EDU_ID,
+                Self::rows(&entries.education, Self::education_row_view),
+                &Self::education_row,
+            )).child(Self::expandable_linear_layout(
                 EXP_ID,
+                Self::rows(&entries.experience, Self::experience_row_view),
                 &Self::experience_row,
-            ))
+            )).child(Self::expandable_linear_layout(
+                PROJECTS_ID,
+                Self::rows(&entries.projects, Self::project_row_view),
+                &Self::project_row,
+            )).child(Self::expandable_linear_layout(
+                PUBS_ID,
+                Self::rows(&entries.publications, Self::publication_row_view),
+                &Self::publication_row,
+            )).child(Self::expandable_linear_layout(
+                CERTS_ID,
+                Self::rows(&entries.certifications, Self::certification_row_view),
+                &Self::certification_row,
+            )).child(Self::expandable_linear_layout(
+                CUSTOM_SECTIONS_ID,
+                Self::rows(&entries.custom_sections, Self::custom_section_row_view),
+                &Self::custom_section_row,
+            )).child(Self::page_setup_layout())
             //TODO should dynamically fit to the content, this is just a hot fix
             .fixed_height(2000)
             .scrollable();
-        self.engine.add_layer(
+        s.add_layer(
             Dialog::around(form)
-                .title("New CV")
-                .button("Create new CV", |s| {
+                .title(title)
+                .button(button, move |s| {
                     if let Some(mut cv) = Self::collect_form_data(s) {
-                        let manager = CVDao::new();
-                        match manager.add_cv(&mut cv) {
-                            Ok(_) => {
-                                println!(
-                                    "CV with id {} added successfully.",
-                                    cv.path.as_ref().unwrap()
-                                );
-                                match render_pdf(&cv) {
-                                    Err(e) => eprintln!("Could not render PDF:\n\t {}", e),
-                                    Ok(_) => println!("CV rendered successfully."),
-                                }
+                        cv.path = path.clone();
+                        cv.profile = link.clone();
+                        match Self::store_and_render(&data_dir, &mut cv) {
+                            Ok(message) => {
+                                s.pop_layer();
+                                browser::refresh(s, &data_dir);
+                                s.add_layer(Dialog::info(message));
                             }
-                            Err(e) => println!("{:?}", e),
+                            Err(err) => browser::show_error(s, &err.to_string()),
                         }
                     }
-                }),
+                }).dismiss_button("Cancel"),
         );
+        if let Some(cv) = stored {
+            Self::fill_form(s, &cv);
+        }
+    }
+
+    fn set_edit_content(s: &mut Cursive, id: &str, content: &str) {
+        s.call_on_id(id, |v: &mut BoxView<EditView>| {
+            v.get_inner_mut().set_content(content.to_string())
+        });
+    }
+
+    // Fills the fields of the form which are not part of the rows built from the stored CV.
+    fn fill_form(s: &mut Cursive, cv: &CV) {
+        Self::set_edit_content(s, "Name", &cv.basic.name);
+        Self::set_edit_content(s, "Surname", &cv.basic.surname);
+        if let Some(ref label) = cv.label {
+            Self::set_edit_content(s, LABEL_ID, label);
+        }
+        if let Some(dob) = cv.basic.dob {
+            s.call_on_id("Date of birth", |d: &mut DateView| d.set_date(dob));
+        }
+        if let Some(idx) = Template::iterator().position(|t| *t == cv.template) {
+            s.call_on_id(TEMPLATE_ID, |v: &mut SelectView<Template>| v.set_selection(idx));
+        }
+        if let Some(idx) = LanguageLayout::iterator().position(|l| *l == cv.language_layout) {
+            s.call_on_id(LANGUAGE_LAYOUT_ID, |v: &mut SelectView<LanguageLayout>| {
+                v.set_selection(idx)
+            });
+        }
+        let paper = &cv.page.paper;
+        let paper_idx = PaperSize::iterator()
+            .position(|size| size == paper)
+            .unwrap_or_else(|| PaperSize::iterator().count());
+        s.call_on_id(PAPER_SIZE_ID, |v: &mut SelectView<String>| v.set_selection(paper_idx));
+        if let PaperSize::Custom { width, height } = paper {
+            Self::set_edit_content(s, PAPER_WIDTH_ID, &width.to_string());
+            Self::set_edit_content(s, PAPER_HEIGHT_ID, &height.to_string());
+        }
+        let margins = &cv.page.margins;
+        let values = [margins.top, margins.bottom, margins.left, margins.right];
+        for (id, value) in MARGIN_IDS.iter().zip(values.iter()) {
+            Self::set_edit_content(s, id, &value.to_string());
+        }
     }
 
-    fn get_data_form_row(view: &mut View) -> Option<String> {
+    fn get_data_form_row(view: &mut dyn View) -> Option<String> {
         let data_index = 1;
         let aux = view
             .as_any_mut()
@@ -269,15 +849,67 @@ impl Graphics {
         Some(data.to_string())
     }
 
-    fn get_date(lin_lay: &mut LinearLayout, idx: usize) -> Option<NaiveDate> {
+    // Stores the CV (a new one is added, a stored one updated) and renders it into a temporary
+    // file. Returns the message describing where both ended up.
+    fn store_and_render(data_dir: &str, cv: &mut CV) -> CVResult<String> {
+        let manager = CVDao::new(data_dir)?;
+        match cv.path {
+            Some(_) => manager.update_cv(cv)?,
+            None => manager.add_cv(cv)?,
+        }
+        let pdf_path = env::temp_dir().join(default_file_name(cv));
+        let warnings = render_pdf_to_file(cv, &pdf_path)?;
+        let message = format!(
+            "CV saved to {}.\nCV rendered to {}.",
+            cv.path.as_ref().unwrap(),
+            pdf_path.display()
+        );
+        Ok(browser::with_warnings(message, &warnings))
+    }
+
+    fn get_date_view(lin_lay: &mut LinearLayout, idx: usize) -> &mut IdView<DateView> {
         lin_lay
             .get_child_mut(idx)
             .unwrap()
             .as_any_mut()
             .downcast_mut::<IdView<DateView>>()
             .unwrap()
-            .get_mut()
-            .retrieve_date()
+    }
+
+    fn get_date(
+        lin_lay: &mut LinearLayout,
+        idx: usize,
+    ) -> Result<Option<NaiveDate>, ValidationError> {
+        Self::get_date_view(lin_lay, idx).get_mut().retrieve_date()
+    }
+
+    // The "To" picker of the span may be marked "until now", in which case the span is ongoing.
+    // Unfilled pickers yield no span, contradictory dates an error.
+    fn get_span(
+        lin_lay: &mut LinearLayout,
+        from: usize,
+        to: usize,
+    ) -> Result<Option<TimeSpan>, ValidationError> {
+        let from = match Self::get_date(lin_lay, from)? {
+            Some(from) => from,
+            None => return Ok(None),
+        };
+        let mut to_view = Self::get_date_view(lin_lay, to).get_mut();
+        if to_view.is_open_ended() {
+            return Ok(Some(TimeSpan::ongoing(from)));
+        }
+        match to_view.retrieve_date()? {
+            Some(to) => TimeSpan::new(from, to).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn errors_to_result<T>(value: T, errors: Vec<String>) -> Result<T, String> {
+        if errors.is_empty() {
+            Ok(value)
+        } else {
+            Err(errors.join("\n"))
+        }
     }
 
     fn collect_contacts(c: &mut Cursive) -> Vec<Contact> {
@@ -321,7 +953,8 @@ impl Graphics {
                                 res.push(Contact::Phone(number));
                             }
                         }
-                        _ => panic!("Unexpected selection."),
+                        // The addresses are collected from their own sub-form.
+                        _ => (),
                     }
                 }
             }),
@@ -329,8 +962,76 @@ impl Graphics {
         res
     }
 
-    fn collect_experience(c: &mut Cursive) -> Vec<Experience> {
+    // The rows left empty are skipped.
+    fn collect_addresses(c: &mut Cursive) -> Result<Vec<Contact>, String> {
+        let mut res = vec![];
+        let mut errors = vec![];
+        let mut addresses_root = c
+            .find_id::<LinearLayout>(ADDRESSES_ID)
+            .expect("Could not find the root of the addresses.");
+        addresses_root.call_on_any(
+            &Selector::Id(ADDRESS_CHILD_ID),
+            Box::new(|s| {
+                if let Some(id_view) = s.downcast_mut::<IdView<LinearLayout>>() {
+                    let mut lin_lay = id_view.get_mut();
+                    let (lines, city, region, postal_code, country) = (0, 1, 2, 3, 4);
+                    let lines: Vec<String> = {
+                        let line_rows = lin_lay
+                            .get_child_mut(lines)
+                            .unwrap()
+                            .as_any_mut()
+                            .downcast_mut::<LinearLayout>()
+                            .unwrap();
+                        (0..line_rows.len())
+                            .filter_map(|idx| {
+                                Self::get_data_form_row(line_rows.get_child_mut(idx).unwrap())
+                            }).map(|line| line.trim().to_string())
+                            .filter(|line| !line.is_empty())
+                            .collect()
+                    };
+                    let mut content = |idx| {
+                        Self::get_data_form_row(lin_lay.get_child_mut(idx).unwrap())
+                            .unwrap_or_default()
+                            .trim()
+                            .to_string()
+                    };
+                    let (city, region, postal_code, country) = (
+                        content(city),
+                        content(region),
+                        content(postal_code),
+                        content(country),
+                    );
+                    let fields = [&city, &region, &postal_code, &country];
+                    if lines.is_empty() && fields.iter().all(|field| field.is_empty()) {
+                        return;
+                    }
+                    let country = match CountryCode::for_alpha2_caseless(&country)
+                        .or_else(|_| CountryCode::for_alpha3_caseless(&country))
+                    {
+                        Ok(country) => country,
+                        Err(_) => {
+                            errors.push(format!(
+                                "Contacts, '{}' is not an ISO 3166 country code.",
+                                country
+                            ));
+                            return;
+                        }
+                    };
+                    let region = Self::optional_text(region);
+                    let postal_code = Self::optional_text(postal_code);
+                    match Address::new(lines, &city, region, postal_code, country) {
+                        Ok(address) => res.push(Contact::Address(address)),
+                        Err(err) => errors.push(format!("Contacts, {}", err)),
+                    }
+                }
+            }),
+        );
+        Self::errors_to_result(res, errors)
+    }
+
+    fn collect_experience(c: &mut Cursive) -> Result<Vec<Experience>, String> {
         let mut res = vec![];
+        let mut errors = vec![];
         let mut experience_root = c
             .find_id::<LinearLayout>(EXP_ID)
             .expect("Could not find the root of the experience.");
@@ -340,8 +1041,10 @@ impl Graphics {
                 if let Some(id_view) = s.downcast_mut::<IdView<LinearLayout>>() {
                     let mut lin_lay = id_view.get_mut();
                     let (from, to, employer, job_name, description) = (0, 1, 2, 3, 4);
-                    let from = Self::get_date(&mut lin_lay, from);
-                    let to = Self::get_date(&mut lin_lay, to);
+                    let span = Self::get_span(&mut lin_lay, from, to).unwrap_or_else(|err| {
+                        errors.push(format!("Experience, {}", err));
+                        None
+                    });
                     let employer = Self::get_data_form_row(
                         lin_lay
                             .get_child_mut(employer)
@@ -357,16 +1060,11 @@ impl Graphics {
                             .get_child_mut(description)
                             .expect("could not retrieve employer row"),
                     );
-                    if let (
-                        Some(from),
-                        Some(to),
-                        Some(employer),
-                        Some(job_name),
-                        Some(description),
-                    ) = (from, to, employer, job_name, description)
+                    if let (Some(span), Some(employer), Some(job_name), Some(description)) =
+                        (span, employer, job_name, description)
                     {
                         res.push(Experience {
-                            span: TimeSpan::new(from, to),
+                            span,
                             employer,
                             job_name,
                             description,
@@ -375,52 +1073,36 @@ impl Graphics {
                 }
             }),
         );
-        res
+        Self::errors_to_result(res, errors)
     }
 
     // Refactor with collect_experience
-    fn collect_education(c: &mut Cursive) -> Vec<Education> {
+    fn collect_education(c: &mut Cursive) -> Result<Vec<Education>, String> {
         let mut res = vec![];
-        let mut experience_root = c
-            .find_id::<LinearLayout>(EXP_ID)
+        let mut errors = vec![];
+        let mut education_root = c
+            .find_id::<LinearLayout>(EDU_ID)
             .expect("Could not find the root of the education.");
-        experience_root.call_on_any(
+        education_root.call_on_any(
             &Selector::Id(EDU_CHILD_ID),
             Box::new(|s| {
                 if let Some(id_view) = s.downcast_mut::<IdView<LinearLayout>>() {
                     let mut lin_lay = id_view.get_mut();
                     let (from, to, uni_name, degree, field_of_study) = (0, 1, 2, 3, 4);
-                    let from = lin_lay
-                        .get_child_mut(from)
-                        .unwrap()
-                        .as_any_mut()
-                        .downcast_mut::<IdView<DateView>>()
-                        .unwrap()
-                        .get_mut()
-                        .retrieve_date();
-                    let to = lin_lay
-                        .get_child_mut(to)
-                        .unwrap()
-                        .as_any_mut()
-                        .downcast_mut::<IdView<DateView>>()
-                        .unwrap()
-                        .get_mut()
-                        .retrieve_date();
+                    let span = Self::get_span(&mut lin_lay, from, to).unwrap_or_else(|err| {
+                        errors.push(format!("Education, {}", err));
+                        None
+                    });
                     let uni_name =
                         Self::get_data_form_row(lin_lay.get_child_mut(uni_name).unwrap());
                     let degree = Self::get_data_form_row(lin_lay.get_child_mut(degree).unwrap());
                     let field_of_study =
                         Self::get_data_form_row(lin_lay.get_child_mut(field_of_study).unwrap());
-                    if let (
-                        Some(from),
-                        Some(to),
-                        Some(uni_name),
-                        Some(degree),
-                        Some(field_of_study),
-                    ) = (from, to, uni_name, degree, field_of_study)
+                    if let (Some(span), Some(uni_name), Some(degree), Some(field_of_study)) =
+                        (span, uni_name, degree, field_of_study)
                     {
                         res.push(Education {
-                            span: TimeSpan::new(from, to),
+                            span,
                             uni_name,
                             degree,
                             field_of_study,
@@ -429,57 +1111,398 @@ impl Graphics {
                 }
             }),
         );
-        res
+        Self::errors_to_result(res, errors)
     }
 
-    fn collect_languages(c: &mut Cursive) -> Vec<Lang> {
+    // The optional fields of the form are left empty when there is no value.
+    fn optional_text(text: String) -> Option<String> {
+        Some(text.trim().to_string()).filter(|text| !text.is_empty())
+    }
+
+    fn optional_url(text: String, section: &str) -> Result<Option<Url>, String> {
+        match Self::optional_text(text) {
+            None => Ok(None),
+            Some(text) => Url::from_str(&text)
+                .map(Some)
+                .map_err(|_| format!("{}, '{}' is not a valid URL.", section, text)),
+        }
+    }
+
+    // The lists are entered as comma separated values.
+    fn comma_separated(text: &str) -> Vec<String> {
+        text.split(',')
+            .map(|item| item.trim().to_string())
+            .filter(|item| !item.is_empty())
+            .collect()
+    }
+
+    fn collect_projects(c: &mut Cursive) -> Result<Vec<Project>, String> {
         let mut res = vec![];
-        let mut experience_root = c
-            .find_id::<LinearLayout>(EXP_ID)
-            .expect("Could not find the root of the education.");
-        experience_root.call_on_any(
-            &Selector::Id(EDU_CHILD_ID),
+        let mut errors = vec![];
+        let mut projects_root = c
+            .find_id::<LinearLayout>(PROJECTS_ID)
+            .expect("Could not find the root of the projects.");
+        projects_root.call_on_any(
+            &Selector::Id(PROJECT_CHILD_ID),
             Box::new(|s| {
                 if let Some(id_view) = s.downcast_mut::<IdView<LinearLayout>>() {
                     let mut lin_lay = id_view.get_mut();
-                    let language = 0;
-                    let proficiency = 1;
-                    let notes = 2;
-                    let language = lin_lay
-                        .get_child_mut(language)
-                        .unwrap()
-                        .as_any_mut()
-                        .downcast_mut::<SelectView<Language>>()
-                        .unwrap()
-                        .selection();
-                    let proficiency = lin_lay
-                        .get_child_mut(proficiency)
-                        .unwrap()
-                        .as_any_mut()
-                        .downcast_mut::<SelectView<LanguageProficiency>>()
-                        .unwrap()
-                        .selection();
-                    let notes = Self::get_data_form_row(lin_lay.get_child_mut(notes).unwrap());
-                    if let (Some(language), Some(proficiency), Some(notes)) =
-                        (language, proficiency, notes)
+                    let (from, to, name, url, role, technologies) = (0, 1, 2, 3, 4, 5);
+                    let span = Self::get_span(&mut lin_lay, from, to).unwrap_or_else(|err| {
+                        errors.push(format!("Projects, {}", err));
+                        None
+                    });
+                    let name = Self::get_data_form_row(lin_lay.get_child_mut(name).unwrap());
+                    let url = Self::get_data_form_row(lin_lay.get_child_mut(url).unwrap())
+                        .map(|url| Self::optional_url(url, "Projects"))
+                        .unwrap_or(Ok(None))
+                        .unwrap_or_else(|err| {
+                            errors.push(err);
+                            None
+                        });
+                    let role = Self::get_data_form_row(lin_lay.get_child_mut(role).unwrap());
+                    let technologies =
+                        Self::get_data_form_row(lin_lay.get_child_mut(technologies).unwrap());
+                    if let (Some(span), Some(name), Some(role), Some(technologies)) =
+                        (span, name, role, technologies)
+                    {
+                        res.push(Project {
+                            span,
+                            name,
+                            url,
+                            role,
+                            technologies: Self::comma_separated(&technologies),
+                        });
+                    }
+                }
+            }),
+        );
+        Self::errors_to_result(res, errors)
+    }
+
+    fn collect_publications(c: &mut Cursive) -> Result<Vec<Publication>, String> {
+        let mut res = vec![];
+        let mut errors = vec![];
+        let mut publications_root = c
+            .find_id::<LinearLayout>(PUBS_ID)
+            .expect("Could not find the root of the publications.");
+        publications_root.call_on_any(
+            &Selector::Id(PUB_CHILD_ID),
+            Box::new(|s| {
+                if let Some(id_view) = s.downcast_mut::<IdView<LinearLayout>>() {
+                    let mut lin_lay = id_view.get_mut();
+                    let (title, venue, date, doi, url, co_authors) = (0, 1, 2, 3, 4, 5);
+                    let title = Self::get_data_form_row(lin_lay.get_child_mut(title).unwrap());
+                    let venue = Self::get_data_form_row(lin_lay.get_child_mut(venue).unwrap());
+                    let date = Self::get_date(&mut lin_lay, date).unwrap_or_else(|err| {
+                        errors.push(format!("Publications, {}", err));
+                        None
+                    });
+                    let doi = Self::get_data_form_row(lin_lay.get_child_mut(doi).unwrap())
+                        .and_then(Self::optional_text);
+                    let url = Self::get_data_form_row(lin_lay.get_child_mut(url).unwrap())
+                        .map(|url| Self::optional_url(url, "Publications"))
+                        .unwrap_or(Ok(None))
+                        .unwrap_or_else(|err| {
+                            errors.push(err);
+                            None
+                        });
+                    let co_authors =
+                        Self::get_data_form_row(lin_lay.get_child_mut(co_authors).unwrap());
+                    if let (Some(title), Some(venue), Some(date), Some(co_authors)) =
+                        (title, venue, date, co_authors)
                     {
-                        if let (Ok(language), Ok(proficiency)) =
-                            (Rc::try_unwrap(language), Rc::try_unwrap(proficiency))
+                        res.push(Publication {
+                            title,
+                            venue,
+                            date,
+                            doi,
+                            url,
+                            co_authors: Self::comma_separated(&co_authors),
+                        });
+                    }
+                }
+            }),
+        );
+        Self::errors_to_result(res, errors)
+    }
+
+    fn collect_certifications(c: &mut Cursive) -> Result<Vec<Certification>, String> {
+        let mut res = vec![];
+        let mut errors = vec![];
+        let mut certifications_root = c
+            .find_id::<LinearLayout>(CERTS_ID)
+            .expect("Could not find the root of the certifications.");
+        certifications_root.call_on_any(
+            &Selector::Id(CERT_CHILD_ID),
+            Box::new(|s| {
+                if let Some(id_view) = s.downcast_mut::<IdView<LinearLayout>>() {
+                    let mut lin_lay = id_view.get_mut();
+                    let (name, issuer, obtained, expires, credential_id) = (0, 1, 2, 3, 4);
+                    let name = Self::get_data_form_row(lin_lay.get_child_mut(name).unwrap());
+                    let issuer = Self::get_data_form_row(lin_lay.get_child_mut(issuer).unwrap());
+                    let obtained = Self::get_date(&mut lin_lay, obtained);
+                    let expires = {
+                        let mut expires_view = Self::get_date_view(&mut lin_lay, expires).get_mut();
+                        if expires_view.is_open_ended() {
+                            Ok(None)
+                        } else {
+                            expires_view.retrieve_date()
+                        }
+                    };
+                    let (obtained, expires) = match (obtained, expires) {
+                        (Ok(obtained), Ok(expires)) => (obtained, expires),
+                        (Err(err), _) | (_, Err(err)) => {
+                            errors.push(format!("Certifications, {}", err));
+                            return;
+                        }
+                    };
+                    let credential_id =
+                        Self::get_data_form_row(lin_lay.get_child_mut(credential_id).unwrap())
+                            .and_then(Self::optional_text);
+                    if let (Some(name), Some(issuer), Some(obtained)) = (name, issuer, obtained) {
+                        match Certification::new(&name, &issuer, obtained, expires, credential_id)
                         {
-                            res.push(Lang {
-                                language,
-                                proficiency,
-                                notes,
-                            });
+                            Ok(certification) => res.push(certification),
+                            Err(err) => errors.push(format!("Certifications, {}", err)),
                         }
                     }
                 }
             }),
         );
-        res
+        Self::errors_to_result(res, errors)
     }
 
-    fn collect_basic_info(c: &mut Cursive) -> Result<BasicInfo, &str> {
+    // The rows left empty are skipped, a value needs the name of the field though.
+    fn collect_custom_fields(c: &mut Cursive) -> Result<Vec<CustomField>, String> {
+        let mut res = vec![];
+        let mut errors = vec![];
+        let mut fields_root = c
+            .find_id::<LinearLayout>(CUSTOM_FIELDS_ID)
+            .expect("Could not find the root of the custom fields.");
+        fields_root.call_on_any(
+            &Selector::Id(CUSTOM_FIELD_CHILD_ID),
+            Box::new(|s| {
+                if let Some(id_view) = s.downcast_mut::<IdView<LinearLayout>>() {
+                    let mut lin_lay = id_view.get_mut();
+                    let mut content = |idx| {
+                        lin_lay
+                            .get_child_mut(idx)
+                            .unwrap()
+                            .as_any_mut()
+                            .downcast_mut::<BoxView<EditView>>()
+                            .unwrap()
+                            .get_inner()
+                            .get_content()
+                            .trim()
+                            .to_string()
+                    };
+                    let (name, value) = (content(0), content(1));
+                    match (name.is_empty(), value.is_empty()) {
+                        (true, true) => (),
+                        (true, false) => errors.push(format!(
+                            "Custom fields, the value '{}' has no field name.",
+                            value
+                        )),
+                        _ => res.push(CustomField { name, value }),
+                    }
+                }
+            }),
+        );
+        Self::errors_to_result(res, errors)
+    }
+
+    // The rows left empty are skipped, the others need a heading.
+    fn collect_custom_entry(lin_lay: &mut LinearLayout) -> Result<Option<CustomEntry>, String> {
+        let (from, to, heading, subheading, body) = (1, 2, 3, 4, 5);
+        let dated = lin_lay
+            .find_id(CUSTOM_ENTRY_DATED_ID, |c: &mut Checkbox| c.is_checked())
+            .unwrap_or(false);
+        let (heading, subheading, body) = {
+            let mut content = |idx| {
+                Self::get_data_form_row(lin_lay.get_child_mut(idx).unwrap()).unwrap_or_default()
+            };
+            (content(heading), content(subheading), content(body))
+        };
+        if heading.trim().is_empty() {
+            if !dated && subheading.trim().is_empty() && body.trim().is_empty() {
+                return Ok(None);
+            }
+            return Err("Custom sections, every entry needs a heading.".to_string());
+        }
+        let span = if dated {
+            Self::get_span(lin_lay, from, to)
+                .map_err(|err| format!("Custom sections, {}", err))?
+        } else {
+            None
+        };
+        Ok(Some(CustomEntry {
+            span,
+            heading,
+            subheading,
+            body,
+        }))
+    }
+
+    // The sections must have a title, the heading is shown above the entries.
+    fn collect_custom_sections(c: &mut Cursive) -> Result<Vec<CustomSection>, String> {
+        let mut res = vec![];
+        let mut errors = vec![];
+        let mut sections_root = c
+            .find_id::<LinearLayout>(CUSTOM_SECTIONS_ID)
+            .expect("Could not find the root of the custom sections.");
+        sections_root.call_on_any(
+            &Selector::Id(CUSTOM_SECTION_CHILD_ID),
+            Box::new(|s| {
+                if let Some(id_view) = s.downcast_mut::<IdView<LinearLayout>>() {
+                    let mut lin_lay = id_view.get_mut();
+                    let title = Self::get_data_form_row(lin_lay.get_child_mut(0).unwrap())
+                        .unwrap_or_default();
+                    let mut entries = vec![];
+                    lin_lay.call_on_any(
+                        &Selector::Id(CUSTOM_ENTRY_CHILD_ID),
+                        Box::new(|s| {
+                            if let Some(id_view) = s.downcast_mut::<IdView<LinearLayout>>() {
+                                match Self::collect_custom_entry(&mut id_view.get_mut()) {
+                                    Ok(entry) => entries.extend(entry),
+                                    Err(err) => errors.push(err),
+                                }
+                            }
+                        }),
+                    );
+                    if title.trim().is_empty() {
+                        errors.push("Custom sections, every section needs a title.".to_string());
+                    } else {
+                        res.push(CustomSection { title, entries });
+                    }
+                }
+            }),
+        );
+        Self::errors_to_result(res, errors)
+    }
+
+    // The selection is taken from the second child of the horizontal row (the first is the
+    // label).
+    fn row_selection<T: 'static>(lin_lay: &mut LinearLayout, idx: usize) -> Option<Rc<T>> {
+        lin_lay
+            .get_child_mut(idx)
+            .unwrap()
+            .as_any_mut()
+            .downcast_mut::<LinearLayout>()
+            .unwrap()
+            .get_child_mut(1)
+            .unwrap()
+            .as_any_mut()
+            .downcast_mut::<SelectView<T>>()
+            .unwrap()
+            .selection()
+    }
+
+    fn collect_languages(c: &mut Cursive) -> Result<Vec<Lang>, String> {
+        let mut res = vec![];
+        let mut errors = vec![];
+        let mut languages_root = c
+            .find_id::<LinearLayout>(LANGS_ID)
+            .expect("Could not find the root of the languages.");
+        languages_root.call_on_any(
+            &Selector::Id(LANG_CHILD_ID),
+            Box::new(|s| {
+                if let Some(id_view) = s.downcast_mut::<IdView<LinearLayout>>() {
+                    let mut lin_lay = id_view.get_mut();
+                    let (language, proficiency, notes, assess) = (0, 1, 2, 3);
+                    // The code is shown by the third child of the language row.
+                    let code = lin_lay
+                        .get_child_mut(language)
+                        .and_then(|row| row.as_any_mut().downcast_mut::<LinearLayout>())
+                        .and_then(|row| row.get_child_mut(2))
+                        .and_then(|view| view.as_any_mut().downcast_mut::<IdView<TextView>>())
+                        .map(|view| view.get_mut().get_content().source().to_string())
+                        .unwrap_or_default();
+                    let level = |lin_lay: &mut LinearLayout, idx| {
+                        Self::row_selection::<&'static LanguageProficiency>(lin_lay, idx)
+                            .map(|level| (*level).clone())
+                    };
+                    let proficiency = level(&mut lin_lay, proficiency);
+                    let notes = Self::get_data_form_row(lin_lay.get_child_mut(notes).unwrap())
+                        .unwrap_or_default();
+                    // The skills follow the checkbox, in the order of LanguageSkills::NAMES.
+                    let assessed = lin_lay
+                        .find_id(LANG_SKILLS_ID, |c: &mut Checkbox| c.is_checked())
+                        .unwrap_or(false);
+                    let skills = if assessed {
+                        let levels = (0..LanguageSkills::NAMES.len())
+                            .map(|i| level(&mut lin_lay, assess + 1 + i))
+                            .collect::<Option<Vec<_>>>();
+                        match levels.and_then(|levels| LanguageSkills::from_levels(&levels)) {
+                            Some(skills) => Some(skills),
+                            None => {
+                                errors.push(
+                                    "Languages, choose the level of every assessed skill."
+                                        .to_string(),
+                                );
+                                return;
+                            }
+                        }
+                    } else {
+                        None
+                    };
+                    let mut certificates = vec![];
+                    lin_lay.call_on_any(
+                        &Selector::Id(LANG_CERT_CHILD_ID),
+                        Box::new(|s| {
+                            if let Some(id_view) = s.downcast_mut::<IdView<LinearLayout>>() {
+                                let mut row = id_view.get_mut();
+                                let mut content = |idx| {
+                                    Self::get_data_form_row(row.get_child_mut(idx).unwrap())
+                                        .unwrap_or_default()
+                                };
+                                let (name, score) = (content(0), content(1));
+                                if name.trim().is_empty() {
+                                    if !score.trim().is_empty() {
+                                        errors.push(
+                                            "Languages, every certificate needs a name."
+                                                .to_string(),
+                                        );
+                                    }
+                                } else {
+                                    certificates.push(LanguageCertificate { name, score });
+                                }
+                            }
+                        }),
+                    );
+                    if code.is_empty() {
+                        errors.push("Languages, choose the language of every row.".to_string());
+                        return;
+                    }
+                    let proficiency = match proficiency {
+                        Some(proficiency) => proficiency,
+                        None => {
+                            errors.push(
+                                "Languages, choose the proficiency of every language.".to_string(),
+                            );
+                            return;
+                        }
+                    };
+                    match Language::from_code(&code) {
+                        Ok(language) => res.push(Lang {
+                            language,
+                            proficiency,
+                            notes,
+                            skills,
+                            certificates,
+                        }),
+                        Err(err) => errors.push(format!("Languages, {}", err)),
+                    }
+                }
+            }),
+        );
+        Self::errors_to_result(res, errors)
+    }
+
+    fn collect_basic_info(
+        c: &mut Cursive,
+        addresses: &[Contact],
+    ) -> Result<BasicInfo, ValidationError> {
         let name = c
             .call_on_id("Name", |s: &mut BoxView<EditView>| {
                 s.get_inner().get_content()
@@ -490,30 +1513,137 @@ impl Graphics {
             }).unwrap();
         let dob = c
             .call_on_id("Date of birth", |s: &mut DateView| s.retrieve_date())
-            .unwrap()
+            .expect("Could not find the date of birth.")?
+            .ok_or_else(|| ValidationError::MissingDate("date of birth".to_string()))?;
+        let mut contacts = Self::collect_contacts(c);
+        contacts.extend_from_slice(addresses);
+        BasicInfo::new(&name, &surname, dob, contacts)
+    }
+
+    fn collect_millimetres(c: &mut Cursive, id: &str) -> Result<f64, String> {
+        let content = c
+            .call_on_id(id, |s: &mut BoxView<EditView>| s.get_inner().get_content())
             .unwrap();
-        let contacts = Self::collect_contacts(c);
-        if contacts.is_empty() {
-            return Err("There must be at least one valid contact filled in.");
+        Self::parse_millimetres(id, &content)
+    }
+
+    // f64 parses "NaN" and "inf" as well, neither is a usable dimension.
+    fn parse_millimetres(id: &str, content: &str) -> Result<f64, String> {
+        match f64::from_str(content.trim()) {
+            Ok(value) if value.is_finite() => Ok(value),
+            _ => Err(format!("{}: '{}' is not a number.", id, content)),
         }
-        Ok(BasicInfo::new(&name, &surname, dob, contacts))
     }
 
-    // This handler is responsible for collecting the data from the "New CV" form.
+    fn collect_page_setup(c: &mut Cursive) -> Result<PageSetup, String> {
+        let selection = c
+            .call_on_id(PAPER_SIZE_ID, |s: &mut SelectView<String>| s.selection())
+            .unwrap()
+            .unwrap();
+        let paper = match PaperSize::iterator().find(|size| size.to_string() == *selection) {
+            Some(size) => size.clone(),
+            None => PaperSize::Custom {
+                width: Self::collect_millimetres(c, PAPER_WIDTH_ID)?,
+                height: Self::collect_millimetres(c, PAPER_HEIGHT_ID)?,
+            },
+        };
+        let [top, bottom, left, right] = MARGIN_IDS;
+        Ok(PageSetup {
+            paper,
+            margins: Margins {
+                top: Self::collect_millimetres(c, top)?,
+                bottom: Self::collect_millimetres(c, bottom)?,
+                left: Self::collect_millimetres(c, left)?,
+                right: Self::collect_millimetres(c, right)?,
+            },
+        })
+    }
+
+    // The label is optional, an empty field means no label.
+    fn collect_label(c: &mut Cursive) -> Option<String> {
+        let label = c
+            .call_on_id(LABEL_ID, |s: &mut BoxView<EditView>| s.get_inner().get_content())
+            .unwrap();
+        Some(label.trim().to_string()).filter(|label| !label.is_empty())
+    }
+
+    fn collect_template(c: &mut Cursive) -> Template {
+        c.call_on_id(TEMPLATE_ID, |s: &mut SelectView<Template>| s.selection())
+            .unwrap()
+            .map(|template| (*template).clone())
+            .unwrap_or_default()
+    }
+
+    fn collect_language_layout(c: &mut Cursive) -> LanguageLayout {
+        c.call_on_id(LANGUAGE_LAYOUT_ID, |s: &mut SelectView<LanguageLayout>| s.selection())
+            .unwrap()
+            .map(|layout| (*layout).clone())
+            .unwrap_or_default()
+    }
+
+    // This handler is responsible for collecting the data from the CV form.
     pub fn collect_form_data(c: &mut Cursive) -> Option<CV> {
-        let mut error = String::new();
-        let basic = match Self::collect_basic_info(c) {
-            Ok(b) => Some(b),
-            Err(e) => {
-                error = e.to_string();
-                None
-            }
+        let mut errors = vec![];
+        let addresses = Self::collect_addresses(c).map_err(|e| errors.push(e)).ok();
+        let basic = addresses.and_then(|addresses| {
+            Self::collect_basic_info(c, &addresses)
+                .map_err(|e| errors.push(e.to_string()))
+                .ok()
+        });
+        let custom_fields = Self::collect_custom_fields(c).map_err(|e| errors.push(e)).ok();
+        let basic = match (basic, custom_fields) {
+            (Some(basic), Some(custom_fields)) => Some(BasicInfo {
+                custom_fields,
+                ..basic
+            }),
+            _ => None,
         };
-        if let Some(basic) = basic {
+        let page = Self::collect_page_setup(c).map_err(|e| errors.push(e)).ok();
+        let experience = Self::collect_experience(c).map_err(|e| errors.push(e)).ok();
+        let education = Self::collect_education(c).map_err(|e| errors.push(e)).ok();
+        let projects = Self::collect_projects(c).map_err(|e| errors.push(e)).ok();
+        let publications = Self::collect_publications(c).map_err(|e| errors.push(e)).ok();
+        let certifications = Self::collect_certifications(c)
+            .map_err(|e| errors.push(e))
+            .ok();
+        let custom_sections = Self::collect_custom_sections(c)
+            .map_err(|e| errors.push(e))
+            .ok();
+        let languages = Self::collect_languages(c).map_err(|e| errors.push(e)).ok();
+        let mut error = errors.join("\n");
+        if let (
+            Some(basic),
+            Some(page),
+            Some(experience),
+            Some(education),
+            Some(projects),
+            Some(publications),
+            Some(certifications),
+            Some(custom_sections),
+            Some(languages),
+        ) = (
+            basic,
+            page,
+            experience,
+            education,
+            projects,
+            publications,
+            certifications,
+            custom_sections,
+            languages,
+        ) {
             return match CVBuilder::default(basic)
-                .experience(Self::collect_experience(c))
-                .education(Self::collect_education(c))
-                .languages(Self::collect_languages(c))
+                .label(Self::collect_label(c))
+                .experience(experience)
+                .education(education)
+                .languages(languages)
+                .projects(projects)
+                .publications(publications)
+                .certifications(certifications)
+                .custom_sections(custom_sections)
+                .page(page)
+                .template(Self::collect_template(c))
+                .language_layout(Self::collect_language_layout(c))
                 .build()
             {
                 Ok(cv) => Some(cv),
@@ -528,78 +1658,48 @@ impl Graphics {
         None
     }
 
-    // Fearlessly stolen from the cursive example.
     fn add_menu(&mut self) {
-        // We'll use a counter to name new files.
-        let counter = AtomicUsize::new(1);
-
-        // The menubar is a list of (label, menu tree) pairs.
+        let data_dir = self.data_dir.clone();
         self.engine
             .menubar()
-            // We add a new "File" tree
             .add_subtree(
                 "File",
                 MenuTree::new()
-                    // Trees are made of leaves, with are directly actionable...
-                    .leaf("New", move |s| {
-                        // Here we use the counter to add an entry
-                        // in the list of "Recent" items.
-                        let i = counter.fetch_add(1, Ordering::Relaxed);
-                        let filename = format!("New {}", i);
-                        s.menubar()
-                            .find_subtree("File")
-                            .unwrap()
-                            .find_subtree("Recent")
-                            .unwrap()
-                            .insert_leaf(0, filename, |_| ());
-
-                        s.add_layer(Dialog::info("New file!"));
-                    })
-                    // ... and of sub-trees, which open up when selected.
-                    .subtree(
-                        "Recent",
-                        // The `.with()` method can help when running loops
-                        // within builder patterns.
-                        MenuTree::new().with(|tree| {
-                            for i in 1..100 {
-                                // We don't actually do anything here,
-                                // but you could!
-                                tree.add_leaf(format!("Item {}", i), |_| ())
-                            }
-                        }),
-                    )
-                    // Delimiter are simple lines between items,
-                    // and cannot be selected.
+                    .leaf("New CV", move |s| Self::open_form(s, &data_dir, None))
                     .delimiter()
-                    .with(|tree| {
-                        for i in 1..10 {
-                            tree.add_leaf(format!("Option {}", i), |_| ());
-                        }
-                    }),
+                    .leaf("Quit", |s| s.quit()),
             ).add_subtree(
                 "Help",
                 MenuTree::new()
-                    .subtree(
-                        "Help",
-                        MenuTree::new()
-                            .leaf("General", |s| s.add_layer(Dialog::info("Help message!")))
-                            .leaf("Online", |s| {
-                                let text = "Google it yourself!\n\
-                                            Kids, these days...";
-                                s.add_layer(Dialog::info(text))
-                            }),
-                    ).leaf("About", |s| s.add_layer(Dialog::info("Cursive v0.0.0"))),
-            ).add_delimiter()
-            .add_leaf("Quit", |s| s.quit());
+                    .leaf("Keys", |s| {
+                        s.add_layer(Dialog::info("Press Esc to get to the menu."))
+                    }).leaf("About", |s| {
+                        let about = format!("cvmaker v{}", env!("CARGO_PKG_VERSION"));
+                        s.add_layer(Dialog::info(about))
+                    }),
+            );
         self.engine.set_autohide_menu(false);
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::Graphics;
+
     #[test]
     fn form_basic_data() {
         // Might need https://github.com/gyscos/Cursive/issues/271 for UI tests.
         // Otherwise, things can get very clunky.
     }
+
+    #[test]
+    fn non_finite_millimetres_rejected() {
+        assert_eq!(Ok(12.5), Graphics::parse_millimetres("Top margin (mm)", " 12.5 "));
+        for content in &["NaN", "inf", "-inf", "ten"] {
+            assert_eq!(
+                Err(format!("Top margin (mm): '{}' is not a number.", content)),
+                Graphics::parse_millimetres("Top margin (mm)", content)
+            );
+        }
+    }
 }
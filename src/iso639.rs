@@ -0,0 +1,274 @@
+// Responsibility: the table of the ISO 639 languages. The languages having an ISO 639-1 code are
+// listed here along with their endonyms, those having only an ISO 639-3 code come from
+// iso639_3 with the English name alone.
+// https://en.wikipedia.org/wiki/List_of_ISO_639-1_codes
+use iso639_3;
+
+// A language of the table. The ISO 639-3 code identifies the language, the other fields may be
+// missing.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LanguageInfo {
+    pub alpha3: &'static str,
+    pub alpha2: Option<&'static str>,
+    // The English name.
+    pub name: &'static str,
+    // The name of the language in the language itself.
+    pub endonym: Option<&'static str>,
+}
+
+// The ISO 639-3 code, the ISO 639-1 code, the English name and the endonym. The codes and the
+// endonyms which do not exist are left empty.
+static LANGUAGES: &[(&str, &str, &str, &str)] = &[
+    ("aar", "aa", "Afar", "Afaraf"),
+    ("abk", "ab", "Abkhazian", "аҧсуа бызшәа"),
+    ("ave", "ae", "Avestan", "avesta"),
+    ("afr", "af", "Afrikaans", "Afrikaans"),
+    ("aka", "ak", "Akan", "Akan"),
+    ("amh", "am", "Amharic", "አማርኛ"),
+    ("arg", "an", "Aragonese", "aragonés"),
+    ("ara", "ar", "Arabic", "العربية"),
+    ("asm", "as", "Assamese", "অসমীয়া"),
+    ("ava", "av", "Avaric", "авар мацӀ"),
+    ("aym", "ay", "Aymara", "aymar aru"),
+    ("aze", "az", "Azerbaijani", "azərbaycan dili"),
+    ("bak", "ba", "Bashkir", "башҡорт теле"),
+    ("bel", "be", "Belarusian", "беларуская мова"),
+    ("bul", "bg", "Bulgarian", "български език"),
+    ("bis", "bi", "Bislama", "Bislama"),
+    ("bam", "bm", "Bambara", "bamanankan"),
+    ("ben", "bn", "Bengali", "বাংলা"),
+    ("bod", "bo", "Tibetan", "བོད་ཡིག"),
+    ("bre", "br", "Breton", "brezhoneg"),
+    ("bos", "bs", "Bosnian", "bosanski jezik"),
+    ("cat", "ca", "Catalan", "català"),
+    ("che", "ce", "Chechen", "нохчийн мотт"),
+    ("cha", "ch", "Chamorro", "Chamoru"),
+    ("cos", "co", "Corsican", "corsu"),
+    ("cre", "cr", "Cree", "ᓀᐦᐃᔭᐍᐏᐣ"),
+    ("ces", "cs", "Czech", "čeština"),
+    ("chu", "cu", "Church Slavonic", "ѩзыкъ словѣньскъ"),
+    ("chv", "cv", "Chuvash", "чӑваш чӗлхи"),
+    ("cym", "cy", "Welsh", "Cymraeg"),
+    ("dan", "da", "Danish", "dansk"),
+    ("deu", "de", "German", "Deutsch"),
+    ("div", "dv", "Divehi", "ދިވެހި"),
+    ("dzo", "dz", "Dzongkha", "རྫོང་ཁ"),
+    ("ewe", "ee", "Ewe", "Eʋegbe"),
+    ("ell", "el", "Greek", "Ελληνικά"),
+    ("eng", "en", "English", "English"),
+    ("epo", "eo", "Esperanto", "Esperanto"),
+    ("spa", "es", "Spanish", "español"),
+    ("est", "et", "Estonian", "eesti"),
+    ("eus", "eu", "Basque", "euskara"),
+    ("fas", "fa", "Persian", "فارسی"),
+    ("ful", "ff", "Fulah", "Fulfulde"),
+    ("fin", "fi", "Finnish", "suomi"),
+    ("fij", "fj", "Fijian", "vosa Vakaviti"),
+    ("fao", "fo", "Faroese", "føroyskt"),
+    ("fra", "fr", "French", "français"),
+    ("fry", "fy", "Western Frisian", "Frysk"),
+    ("gle", "ga", "Irish", "Gaeilge"),
+    ("gla", "gd", "Scottish Gaelic", "Gàidhlig"),
+    ("glg", "gl", "Galician", "galego"),
+    ("grn", "gn", "Guarani", "Avañe'ẽ"),
+    ("guj", "gu", "Gujarati", "ગુજરાતી"),
+    ("glv", "gv", "Manx", "Gaelg"),
+    ("hau", "ha", "Hausa", "Hausa"),
+    ("heb", "he", "Hebrew", "עברית"),
+    ("hin", "hi", "Hindi", "हिन्दी"),
+    ("hmo", "ho", "Hiri Motu", "Hiri Motu"),
+    ("hrv", "hr", "Croatian", "hrvatski"),
+    ("hat", "ht", "Haitian Creole", "Kreyòl ayisyen"),
+    ("hun", "hu", "Hungarian", "magyar"),
+    ("hye", "hy", "Armenian", "Հայերեն"),
+    ("her", "hz", "Herero", "Otjiherero"),
+    ("ina", "ia", "Interlingua", "Interlingua"),
+    ("ind", "id", "Indonesian", "Bahasa Indonesia"),
+    ("ile", "ie", "Interlingue", "Interlingue"),
+    ("ibo", "ig", "Igbo", "Asụsụ Igbo"),
+    ("iii", "ii", "Sichuan Yi", "ꆈꌠꉙ"),
+    ("ipk", "ik", "Inupiaq", "Iñupiaq"),
+    ("ido", "io", "Ido", "Ido"),
+    ("isl", "is", "Icelandic", "íslenska"),
+    ("ita", "it", "Italian", "italiano"),
+    ("iku", "iu", "Inuktitut", "ᐃᓄᒃᑎᑐᑦ"),
+    ("jpn", "ja", "Japanese", "日本語"),
+    ("jav", "jv", "Javanese", "basa Jawa"),
+    ("kat", "ka", "Georgian", "ქართული"),
+    ("kon", "kg", "Kongo", "Kikongo"),
+    ("kik", "ki", "Kikuyu", "Gĩkũyũ"),
+    ("kua", "kj", "Kuanyama", "Kuanyama"),
+    ("kaz", "kk", "Kazakh", "қазақ тілі"),
+    ("kal", "kl", "Kalaallisut", "kalaallisut"),
+    ("khm", "km", "Khmer", "ខ្មែរ"),
+    ("kan", "kn", "Kannada", "ಕನ್ನಡ"),
+    ("kor", "ko", "Korean", "한국어"),
+    ("kau", "kr", "Kanuri", "Kanuri"),
+    ("kas", "ks", "Kashmiri", "कॉशुर"),
+    ("kur", "ku", "Kurdish", "Kurdî"),
+    ("kom", "kv", "Komi", "коми кыв"),
+    ("cor", "kw", "Cornish", "Kernewek"),
+    ("kir", "ky", "Kyrgyz", "кыргызча"),
+    ("lat", "la", "Latin", "latine"),
+    ("ltz", "lb", "Luxembourgish", "Lëtzebuergesch"),
+    ("lug", "lg", "Ganda", "Luganda"),
+    ("lim", "li", "Limburgish", "Limburgs"),
+    ("lin", "ln", "Lingala", "lingála"),
+    ("lao", "lo", "Lao", "ພາສາລາວ"),
+    ("lit", "lt", "Lithuanian", "lietuvių kalba"),
+    ("lub", "lu", "Luba-Katanga", "Kiluba"),
+    ("lav", "lv", "Latvian", "latviešu valoda"),
+    ("mlg", "mg", "Malagasy", "fiteny malagasy"),
+    ("mah", "mh", "Marshallese", "Kajin M̧ajeļ"),
+    ("mri", "mi", "Maori", "te reo Māori"),
+    ("mkd", "mk", "Macedonian", "македонски јазик"),
+    ("mal", "ml", "Malayalam", "മലയാളം"),
+    ("mon", "mn", "Mongolian", "Монгол хэл"),
+    ("mar", "mr", "Marathi", "मराठी"),
+    ("msa", "ms", "Malay", "Bahasa Melayu"),
+    ("mlt", "mt", "Maltese", "Malti"),
+    ("mya", "my", "Burmese", "ဗမာစာ"),
+    ("nau", "na", "Nauru", "Dorerin Naoero"),
+    ("nob", "nb", "Norwegian Bokmål", "norsk bokmål"),
+    ("nde", "nd", "North Ndebele", "isiNdebele"),
+    ("nep", "ne", "Nepali", "नेपाली"),
+    ("ndo", "ng", "Ndonga", "Owambo"),
+    ("nld", "nl", "Dutch", "Nederlands"),
+    ("nno", "nn", "Norwegian Nynorsk", "norsk nynorsk"),
+    ("nor", "no", "Norwegian", "norsk"),
+    ("nbl", "nr", "South Ndebele", "isiNdebele"),
+    ("nav", "nv", "Navajo", "Diné bizaad"),
+    ("nya", "ny", "Chichewa", "chiCheŵa"),
+    ("oci", "oc", "Occitan", "occitan"),
+    ("oji", "oj", "Ojibwa", "ᐊᓂᔑᓈᐯᒧᐎᓐ"),
+    ("orm", "om", "Oromo", "Afaan Oromoo"),
+    ("ori", "or", "Oriya", "ଓଡ଼ିଆ"),
+    ("oss", "os", "Ossetian", "ирон æвзаг"),
+    ("pan", "pa", "Punjabi", "ਪੰਜਾਬੀ"),
+    ("pli", "pi", "Pali", "पाऴि"),
+    ("pol", "pl", "Polish", "polski"),
+    ("pus", "ps", "Pashto", "پښتو"),
+    ("por", "pt", "Portuguese", "português"),
+    ("que", "qu", "Quechua", "Runa Simi"),
+    ("roh", "rm", "Romansh", "rumantsch grischun"),
+    ("run", "rn", "Kirundi", "Ikirundi"),
+    ("ron", "ro", "Romanian", "română"),
+    ("rus", "ru", "Russian", "русский"),
+    ("kin", "rw", "Kinyarwanda", "Ikinyarwanda"),
+    ("san", "sa", "Sanskrit", "संस्कृतम्"),
+    ("srd", "sc", "Sardinian", "sardu"),
+    ("snd", "sd", "Sindhi", "सिन्धी"),
+    ("sme", "se", "Northern Sami", "Davvisámegiella"),
+    ("sag", "sg", "Sango", "yângâ tî sängö"),
+    ("sin", "si", "Sinhala", "සිංහල"),
+    ("slk", "sk", "Slovak", "slovenčina"),
+    ("slv", "sl", "Slovenian", "slovenščina"),
+    ("smo", "sm", "Samoan", "gagana fa'a Samoa"),
+    ("sna", "sn", "Shona", "chiShona"),
+    ("som", "so", "Somali", "Soomaaliga"),
+    ("sqi", "sq", "Albanian", "Shqip"),
+    ("srp", "sr", "Serbian", "српски језик"),
+    ("ssw", "ss", "Swati", "SiSwati"),
+    ("sot", "st", "Southern Sotho", "Sesotho"),
+    ("sun", "su", "Sundanese", "Basa Sunda"),
+    ("swe", "sv", "Swedish", "svenska"),
+    ("swa", "sw", "Swahili", "Kiswahili"),
+    ("tam", "ta", "Tamil", "தமிழ்"),
+    ("tel", "te", "Telugu", "తెలుగు"),
+    ("tgk", "tg", "Tajik", "тоҷикӣ"),
+    ("tha", "th", "Thai", "ไทย"),
+    ("tir", "ti", "Tigrinya", "ትግርኛ"),
+    ("tuk", "tk", "Turkmen", "Türkmençe"),
+    ("tgl", "tl", "Tagalog", "Wikang Tagalog"),
+    ("tsn", "tn", "Tswana", "Setswana"),
+    ("ton", "to", "Tonga", "faka Tonga"),
+    ("tur", "tr", "Turkish", "Türkçe"),
+    ("tso", "ts", "Tsonga", "Xitsonga"),
+    ("tat", "tt", "Tatar", "татар теле"),
+    ("twi", "tw", "Twi", "Twi"),
+    ("tah", "ty", "Tahitian", "Reo Tahiti"),
+    ("uig", "ug", "Uyghur", "ئۇيغۇرچە"),
+    ("ukr", "uk", "Ukrainian", "українська"),
+    ("urd", "ur", "Urdu", "اردو"),
+    ("uzb", "uz", "Uzbek", "oʻzbek"),
+    ("ven", "ve", "Venda", "Tshivenḓa"),
+    ("vie", "vi", "Vietnamese", "Tiếng Việt"),
+    ("vol", "vo", "Volapük", "Volapük"),
+    ("wln", "wa", "Walloon", "walon"),
+    ("wol", "wo", "Wolof", "Wollof"),
+    ("xho", "xh", "Xhosa", "isiXhosa"),
+    ("yid", "yi", "Yiddish", "ייִדיש"),
+    ("yor", "yo", "Yoruba", "Yorùbá"),
+    ("zha", "za", "Zhuang", "Saɯ cueŋƅ"),
+    ("zho", "zh", "Chinese", "中文"),
+    ("zul", "zu", "Zulu", "isiZulu"),
+    // Reserved for the languages which have no code yet, the "other" language.
+    ("mis", "", "Uncoded languages", ""),
+];
+
+fn nonempty(text: &'static str) -> Option<&'static str> {
+    Some(text).filter(|text| !text.is_empty())
+}
+
+fn info(entry: &(&'static str, &'static str, &'static str, &'static str)) -> LanguageInfo {
+    let (alpha3, alpha2, name, endonym) = *entry;
+    LanguageInfo {
+        alpha3,
+        alpha2: nonempty(alpha2),
+        name,
+        endonym: nonempty(endonym),
+    }
+}
+
+pub fn all() -> impl Iterator<Item = LanguageInfo> {
+    let others = iso639_3::LANGUAGES
+        .iter()
+        .map(|&(alpha3, name)| LanguageInfo {
+            alpha3,
+            alpha2: None,
+            name,
+            endonym: None,
+        });
+    LANGUAGES.iter().map(info).chain(others)
+}
+
+pub fn for_alpha2(code: &str) -> Option<LanguageInfo> {
+    all().find(|language| language.alpha2 == Some(code))
+}
+
+pub fn for_alpha3(code: &str) -> Option<LanguageInfo> {
+    all().find(|language| language.alpha3 == code)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn codes_unique_and_well_formed() {
+        let mut alpha3 = HashSet::new();
+        let mut alpha2 = HashSet::new();
+        for language in all() {
+            assert_eq!(3, language.alpha3.len());
+            assert!(alpha3.insert(language.alpha3), "{} listed twice", language.alpha3);
+            if let Some(code) = language.alpha2 {
+                assert_eq!(2, code.len());
+                assert!(alpha2.insert(code), "{} listed twice", code);
+            }
+        }
+        assert_eq!(183, alpha2.len());
+        // Every individual, macro and special language of ISO 639-3.
+        assert_eq!(7910, alpha3.len());
+    }
+
+    #[test]
+    fn lookup_by_either_code() {
+        assert_eq!(Some("ukr"), for_alpha2("uk").map(|language| language.alpha3));
+        assert_eq!(Some("magyar"), for_alpha3("hun").and_then(|language| language.endonym));
+        assert_eq!(None, for_alpha2("xx"));
+        assert_eq!(Some("Silesian"), for_alpha3("szl").map(|language| language.name));
+        // Reserved for local use, not assigned to any language.
+        assert_eq!(None, for_alpha3("qaa"));
+    }
+}
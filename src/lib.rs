@@ -8,6 +8,7 @@ extern crate isocountry;
 extern crate open;
 extern crate phonenumber;
 extern crate printpdf;
+extern crate rusttype;
 extern crate url;
 extern crate url_serde;
 extern crate uuid;
@@ -17,70 +18,125 @@ extern crate serde_derive;
 extern crate serde;
 extern crate serde_json;
 extern crate serde_test;
+extern crate toml;
 
 mod base;
+mod cli;
+mod config;
 mod dao;
+mod error;
 mod graphics;
+mod iso639;
+mod iso639_3;
 mod renderer;
+mod schema;
+use config::{Config, LEGACY_DATA_DIR};
 use cursive::Cursive;
+use dao::CVDao;
+pub use error::{CVError, CVResult};
 use graphics::Graphics;
 use open::that as open_url;
-use std::error::Error;
+use std::io;
 
-pub fn run() -> Result<(), Box<Error>> {
-    Graphics::new(Cursive::default()).run()
+// The arguments are the command line arguments without the program name. The interactive
+// interface is opened unless the arguments contain a command.
+pub fn run(args: &[String]) -> CVResult<()> {
+    let config = Config::resolve(args)?;
+    let data_dir = config.data_dir_str();
+    if cli::has_command(args) {
+        if cli::touches_storage(args) {
+            if let Some(notice) = migrate_legacy_cvs(&data_dir)? {
+                eprintln!("{}", notice);
+            }
+        }
+        return cli::run(&data_dir, args, &mut io::stdout());
+    }
+    let notice = migrate_legacy_cvs(&data_dir)?;
+    let mut graphics = Graphics::new(Cursive::default(), &data_dir);
+    if let Some(notice) = notice {
+        graphics.notify(&notice);
+    }
+    graphics.run()
     //_run_mocked_renderer();
 }
 
-fn _run_mocked_renderer() -> Result<(), Box<Error>> {
+// Moves the CVs stored by the older versions into the data directory. Returns the notice for
+// the user if there were any.
+fn migrate_legacy_cvs(data_dir: &str) -> CVResult<Option<String>> {
+    let migrated = CVDao::new(data_dir)?.migrate_from(LEGACY_DATA_DIR)?;
+    if migrated == 0 {
+        return Ok(None);
+    }
+    Ok(Some(format!(
+        "{} CV(s) moved from {} to {}.",
+        migrated, LEGACY_DATA_DIR, data_dir
+    )))
+}
+
+fn _run_mocked_renderer() -> CVResult<()> {
     use base::*;
     use chrono::NaiveDate;
-    use renderer::render_pdf;
+    use renderer::{default_file_name, render_pdf_to_file};
+    use std::env;
 
-    let email = Contact::Email(EmailAddress::from("peter@raskolnikov.ru").unwrap());
+    let email = Contact::Email(EmailAddress::from("peter@raskolnikov.ru")?);
     let basic_info = BasicInfo::new(
         "Peter",
         "Raskolnikov",
         NaiveDate::from_ymd(2000, 1, 1),
         vec![email],
-    );
-    render_pdf(
-        &CVBuilder::default(basic_info)
-            .languages(vec![
-                Lang {
-                    language: Language::Russian,
-                    proficiency: LanguageProficiency::C2,
-                    notes: "native speaker".to_string(),
-                },
-                Lang {
-                    language: Language::Arabic,
-                    proficiency: LanguageProficiency::C1,
-                    notes: String::new(),
-                },
-                Lang {
-                    language: Language::English,
-                    proficiency: LanguageProficiency::B1,
-                    notes: "capable of basic communication".to_string(),
-                },
-            ]).experience(vec![Experience {
-                span: TimeSpan::new(
-                    NaiveDate::from_ymd(2015, 5, 1),
-                    NaiveDate::from_ymd(2016, 12, 15),
-                ),
-                employer: "ABC, inc.".to_string(),
-                job_name: "Translator".to_string(),
-                description: "Yes, I translate stuff. I'm a translator.".to_string(),
-            }]).education(vec![Education {
-                span: TimeSpan::new(
-                    NaiveDate::from_ymd(2011, 9, 1),
-                    NaiveDate::from_ymd(2014, 6, 1),
-                ),
-                uni_name: "Cambridge".to_string(),
-                degree: "Master of Arts".to_string(),
-                field_of_study: "Applied linguistics".to_string(),
-            }]).build()
-            .unwrap(),
-    ).expect("Could not render the PDF properly.");
-    open_url("/tmp/test_cv.pdf").expect("Could not open rendered PDF.");
-    Ok(())
+    )?;
+    let cv = CVBuilder::default(basic_info)
+        .languages(vec![
+            Lang {
+                language: Language::from_code("rus")?,
+                proficiency: LanguageProficiency::C2,
+                notes: "native speaker".to_string(),
+                skills: None,
+                certificates: vec![],
+            },
+            Lang {
+                language: Language::from_code("ara")?,
+                proficiency: LanguageProficiency::C1,
+                notes: String::new(),
+                skills: Some(LanguageSkills {
+                    spoken_production: LanguageProficiency::B2,
+                    writing: LanguageProficiency::B2,
+                    ..LanguageSkills::uniform(&LanguageProficiency::C1)
+                }),
+                certificates: vec![],
+            },
+            Lang {
+                language: Language::from_code("eng")?,
+                proficiency: LanguageProficiency::B1,
+                notes: "capable of basic communication".to_string(),
+                skills: None,
+                certificates: vec![LanguageCertificate {
+                    name: "IELTS".to_string(),
+                    score: "5.5".to_string(),
+                }],
+            },
+        ]).experience(vec![Experience {
+            span: TimeSpan::new(
+                NaiveDate::from_ymd(2015, 5, 1),
+                NaiveDate::from_ymd(2016, 12, 15),
+            )?,
+            employer: "ABC, inc.".to_string(),
+            job_name: "Translator".to_string(),
+            description: "Yes, I translate stuff. I'm a translator.".to_string(),
+        }]).education(vec![Education {
+            span: TimeSpan::new(
+                NaiveDate::from_ymd(2011, 9, 1),
+                NaiveDate::from_ymd(2014, 6, 1),
+            )?,
+            uni_name: "Cambridge".to_string(),
+            degree: "Master of Arts".to_string(),
+            field_of_study: "Applied linguistics".to_string(),
+        }]).build()
+        .unwrap();
+    let pdf_path = env::temp_dir().join(default_file_name(&cv));
+    render_pdf_to_file(&cv, &pdf_path)?;
+    open_url(&pdf_path)
+        .map(|_| ())
+        .map_err(|err| CVError::io(&pdf_path.to_string_lossy(), err))
 }
@@ -9,8 +9,14 @@
 //
 extern crate cvmaker;
 
+use std::env;
+use std::process;
+
 fn main() {
-    if let Err(e) = cvmaker::run() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if let Err(e) = cvmaker::run(&args) {
         eprintln!("Application error : {}", e);
+        // The scripts calling the application need to know that the command failed.
+        process::exit(1);
     }
 }
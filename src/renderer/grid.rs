@@ -0,0 +1,183 @@
+// Responsibility: the Europass self-assessment grid of the languages. Every language gets a row
+// with the level of each skill, its certificates and notes follow below the row.
+use super::{
+    language_details, FontType, RenderParams, Renderer, RendererCoordinates, RendererResult,
+};
+use base::{Lang, LanguageSkills};
+use printpdf::Mm;
+
+// Fraction of the grid width taken by the column with the language names, the skills share the
+// rest evenly.
+const NAMES_RATIO: f64 = 0.3;
+// Used in the header when the names of the skills do not fit in the cells, they are explained
+// by a legend below the grid.
+const SHORT_NAMES: [&str; 5] = ["L", "R", "SI", "SP", "W"];
+
+// The cells of a single row, each wrapped to the width of its column. The skill levels are
+// centered in their cells, the language names are not.
+struct GridRow {
+    cells: Vec<Vec<String>>,
+    f_type: FontType,
+}
+
+impl GridRow {
+    fn line_count(&self) -> usize {
+        self.cells.iter().map(Vec::len).max().unwrap_or(0)
+    }
+}
+
+impl<'a> Renderer<'a> {
+    pub(super) fn render_language_grid(
+        &mut self,
+        languages: &[Lang],
+        indent: f64,
+    ) -> RendererResult {
+        if languages.is_empty() {
+            return Ok(());
+        }
+        let label_params = RenderParams::default().with_font_type(FontType::Italic);
+        let short = !self.skill_names_fit(indent);
+        let names = if short {
+            SHORT_NAMES
+        } else {
+            LanguageSkills::NAMES
+        };
+        let mut header = vec![""];
+        header.extend(names.iter());
+        let header = self.grid_row(&header, FontType::Bold, indent);
+        // Do not leave the label and the header orphaned at the bottom of a page.
+        let first_height = self.language_height(&languages[0], indent);
+        self.ensure_space(
+            self.text_height("Languages", &label_params)
+                + self.row_height(&header)
+                + first_height,
+        );
+        self.render_text("Languages", label_params);
+        self.render_grid_row(&header, indent);
+        for lang in languages {
+            self.ensure_space(self.language_height(lang, indent));
+            let row = self.language_row(lang, indent);
+            self.render_grid_row(&row, indent);
+            for block in language_details(lang, indent) {
+                self.render_block(&block);
+            }
+        }
+        if short {
+            let legend = SHORT_NAMES
+                .iter()
+                .zip(LanguageSkills::NAMES.iter())
+                .map(|(short, name)| format!("{}: {}", short, name.to_lowercase()))
+                .collect::<Vec<_>>()
+                .join(", ");
+            self.render_text(&legend, RenderParams::default().with_offset(indent));
+        }
+        Ok(())
+    }
+
+    // The left edge of every column relative to the grid start and the width of the skill
+    // columns.
+    fn grid_columns(&self, indent: f64) -> (Vec<Mm>, Mm) {
+        let Mm(width) = self.column_end() - self.text_start(Some(indent));
+        let names_width = width * NAMES_RATIO;
+        let skill_width = (width - names_width) / LanguageSkills::NAMES.len() as f64;
+        let mut starts = vec![Mm(0.0)];
+        starts.extend((0..SHORT_NAMES.len()).map(|i| Mm(names_width + skill_width * i as f64)));
+        (starts, Mm(skill_width))
+    }
+
+    // The names may take several lines but no word may be broken.
+    fn skill_names_fit(&self, indent: f64) -> bool {
+        let (_, skill_width) = self.grid_columns(indent);
+        LanguageSkills::NAMES
+            .iter()
+            .flat_map(|name| name.split(' '))
+            .all(|word| self.text_width(word, &FontType::Bold) <= skill_width)
+    }
+
+    fn grid_row(&self, texts: &[&str], f_type: FontType, indent: f64) -> GridRow {
+        let (starts, skill_width) = self.grid_columns(indent);
+        let names_width = starts[1];
+        let cells = texts
+            .iter()
+            .enumerate()
+            .map(|(i, text)| {
+                let width = if i == 0 { names_width } else { skill_width };
+                self.wrap_text_to_width(text, &f_type, width)
+            }).collect();
+        GridRow { cells, f_type }
+    }
+
+    fn language_row(&self, lang: &Lang, indent: f64) -> GridRow {
+        let name = lang.language.to_string();
+        let skills = lang.skill_levels();
+        let mut texts = vec![name.as_str()];
+        texts.extend(skills.levels().iter().map(|level| level.level()));
+        self.grid_row(&texts, FontType::Normal, indent)
+    }
+
+    fn row_height(&self, row: &GridRow) -> Mm {
+        self.style.line_height() * row.line_count() as f64
+    }
+
+    // The row of the language along with its certificates and notes.
+    fn language_height(&self, lang: &Lang, indent: f64) -> Mm {
+        language_details(lang, indent)
+            .iter()
+            .fold(self.row_height(&self.language_row(lang, indent)), |acc, block| {
+                acc + self.block_height(block)
+            })
+    }
+
+    fn render_grid_row(&mut self, row: &GridRow, indent: f64) {
+        let (starts, skill_width) = self.grid_columns(indent);
+        let grid_start = self.text_start(Some(indent));
+        let line_height = self.style.line_height();
+        for line in 0..row.line_count() {
+            for (i, cell) in row.cells.iter().enumerate() {
+                if let Some(text) = cell.get(line) {
+                    let mut x = grid_start + starts[i];
+                    if i > 0 {
+                        x += (skill_width - self.text_width(text, &row.f_type)) / 2.0;
+                    }
+                    self.write_line_at(text, x, &row.f_type);
+                }
+            }
+            self.move_cursor_with_offset(&RendererCoordinates {
+                row: line_height,
+                col: Mm(0.0),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use base::test::{assessed_lang_factory, basic_cv_factory};
+    use printpdf::Mm;
+    use renderer::test::with_renderer;
+    use renderer::Style;
+
+    #[test]
+    fn grid_levels_follow_skills() {
+        with_renderer(&basic_cv_factory(), |renderer| {
+            let row = renderer.language_row(&assessed_lang_factory(), 0.0);
+            let levels = row.cells[1..]
+                .iter()
+                .map(|cell| cell[0].as_str())
+                .collect::<Vec<_>>();
+            assert_eq!(vec!["B2", "B2", "B2", "B1", "B1"], levels);
+            assert_eq!(vec!["German".to_string()], row.cells[0]);
+        });
+    }
+
+    #[test]
+    fn skill_names_shortened_in_narrow_grid() {
+        with_renderer(&basic_cv_factory(), |renderer| {
+            renderer.style = Style::compact();
+            assert!(renderer.skill_names_fit(0.0));
+            let left = renderer.column.left;
+            renderer.set_column(left, Mm(50.0));
+            assert!(!renderer.skill_names_fit(0.0));
+        });
+    }
+}
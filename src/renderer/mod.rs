@@ -0,0 +1,1319 @@
+// Responsibility: the program will create a PDF file based on the data given by the user.
+use self::template::layout_for;
+use base::{
+    Certification, CustomEntry, Education, Experience, Lang, LanguageLayout, PageSetup, Project,
+    Publication, CV,
+};
+use error::{CVError, CVResult};
+use printpdf::{
+    types::pdf_layer::PdfLayerReference,
+    types::plugins::graphics::two_dimensional::IndirectFontRef, Mm, PdfDocument,
+    PdfDocumentReference, Pt,
+};
+use rusttype::{Font, Scale};
+use std::env;
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::{BufWriter, Cursor, Write};
+use std::path::{Path, PathBuf};
+
+mod grid;
+mod template;
+
+type RendererResult = CVResult<()>;
+
+// Indentation of the language notes and certificates relative to the language itself.
+const NOTES_INDENT: f64 = 10.0;
+// Minimal blank space between a text and the right-aligned text on the same line.
+const RIGHT_ALIGNED_GAP: Mm = Mm(5.0);
+
+// The OpenSans family is compiled into the binary so that rendering does not depend on the
+// working directory the application was started from.
+static REGULAR_FONT: &[u8] = include_bytes!("../resources/fonts/OpenSans-Regular.ttf");
+static ITALIC_FONT: &[u8] = include_bytes!("../resources/fonts/OpenSans-LightItalic.ttf");
+static BOLD_FONT: &[u8] = include_bytes!("../resources/fonts/OpenSans-Bold.ttf");
+
+// Simple wrapper to be used with the printpdf library. Besides the paper dimensions, it holds
+// the margins every page keeps blank.
+struct SheetDim {
+    width: Mm,
+    height: Mm,
+    top: Mm,
+    bottom: Mm,
+    left: Mm,
+    right: Mm,
+}
+
+impl SheetDim {
+    fn from_page_setup(setup: &PageSetup) -> CVResult<SheetDim> {
+        let (width, height) = setup.paper.dimensions();
+        let margins = &setup.margins;
+        if !width.is_finite() || !height.is_finite() || width <= 0.0 || height <= 0.0 {
+            return Err(CVError::rendering(&format!("Invalid paper size {}.", setup.paper)));
+        }
+        if [margins.top, margins.bottom, margins.left, margins.right]
+            .iter()
+            .any(|margin| !margin.is_finite() || *margin < 0.0)
+        {
+            return Err(CVError::rendering("Margins have to be non-negative numbers."));
+        }
+        let dim = SheetDim {
+            width: Mm(width),
+            height: Mm(height),
+            top: Mm(margins.top),
+            bottom: Mm(margins.bottom),
+            left: Mm(margins.left),
+            right: Mm(margins.right),
+        };
+        if dim.content_width() <= Mm(0.0) || dim.content_height() < Style::regular().line_height() {
+            return Err(CVError::rendering(&format!(
+                "The margins leave no room for the content on paper {}.",
+                setup.paper
+            )));
+        }
+        Ok(dim)
+    }
+
+    // Width of the area between the left and the right margin.
+    fn content_width(&self) -> Mm {
+        self.width - self.left - self.right
+    }
+
+    // Height of the area between the top and the bottom margin.
+    fn content_height(&self) -> Mm {
+        self.height - self.top - self.bottom
+    }
+}
+
+struct RendererCoordinates {
+    col: Mm,
+    row: Mm,
+}
+
+impl RendererCoordinates {
+    pub fn start(dim: &SheetDim) -> RendererCoordinates {
+        RendererCoordinates {
+            col: dim.left,
+            row: dim.height - dim.top,
+        }
+    }
+}
+
+// Font size and the distance between two consecutive baselines, both in points. Templates
+// pick the style the whole CV is set in.
+#[derive(Clone, Copy)]
+struct Style {
+    font_size: i64,
+    line_height: i64,
+}
+
+impl Style {
+    fn regular() -> Style {
+        Style {
+            font_size: 15,
+            line_height: 18,
+        }
+    }
+
+    fn compact() -> Style {
+        Style {
+            font_size: 11,
+            line_height: 13,
+        }
+    }
+
+    fn line_height(&self) -> Mm {
+        Pt(self.line_height as f64).into()
+    }
+}
+
+// Part of the page between the margins the text is currently laid out in. Single column
+// templates use the whole content width, the others split it.
+#[derive(Clone, Copy)]
+struct Column {
+    left: Mm,
+    width: Mm,
+}
+
+// A piece of text wrapped by the renderer on its own. It may carry another text which is set
+// flush with the right edge of the column on the first line of the block, e.g. a time span.
+struct TextBlock {
+    text: String,
+    params: RenderParams,
+    right_aligned: Option<(String, FontType)>,
+}
+
+impl TextBlock {
+    fn new(text: &str, params: RenderParams) -> TextBlock {
+        TextBlock {
+            text: text.to_string(),
+            params,
+            right_aligned: None,
+        }
+    }
+
+    fn with_right_aligned(mut self, text: &str, f_type: FontType) -> TextBlock {
+        self.right_aligned = Some((text.to_string(), f_type));
+        self
+    }
+}
+
+trait Renderable {
+    // Text blocks the object consists of, in the order they are rendered. The indent is chosen
+    // by the template and, as the other offsets, is relative to the left edge of the current
+    // column.
+    fn text_blocks(&self, indent: f64) -> Vec<TextBlock>;
+
+    fn render_object(&self, renderer: &mut Renderer, indent: f64) {
+        for block in self.text_blocks(indent) {
+            renderer.render_block(&block);
+        }
+    }
+
+    // Vertical space the object takes up once rendered. The renderer uses this to decide
+    // whether the object still fits on the current page.
+    fn height(&self, renderer: &Renderer, indent: f64) -> Mm {
+        self.text_blocks(indent)
+            .iter()
+            .fold(Mm(0.0), |acc, block| acc + renderer.block_height(block))
+    }
+}
+
+impl Renderable for Experience {
+    fn text_blocks(&self, indent: f64) -> Vec<TextBlock> {
+        vec![
+            TextBlock::new(
+                &self.employer,
+                RenderParams::default()
+                    .with_font_type(FontType::Bold)
+                    .with_offset(indent),
+            ).with_right_aligned(&self.span.to_string(), FontType::Normal),
+            TextBlock::new(
+                &self.job_name,
+                RenderParams::default()
+                    .with_font_type(FontType::Italic)
+                    .with_offset(indent),
+            ),
+            TextBlock::new(
+                &self.description,
+                RenderParams::default().with_offset(indent),
+            ),
+        ]
+    }
+}
+
+impl Renderable for Education {
+    fn text_blocks(&self, indent: f64) -> Vec<TextBlock> {
+        vec![
+            TextBlock::new(
+                &self.field_of_study,
+                RenderParams::default()
+                    .with_font_type(FontType::Bold)
+                    .with_offset(indent),
+            ).with_right_aligned(&self.span.to_string(), FontType::Normal),
+            TextBlock::new(
+                &self.degree,
+                RenderParams::default()
+                    .with_font_type(FontType::Italic)
+                    .with_offset(indent),
+            ),
+            TextBlock::new(&self.uni_name, RenderParams::default().with_offset(indent)),
+        ]
+    }
+}
+
+impl Renderable for Lang {
+    fn text_blocks(&self, indent: f64) -> Vec<TextBlock> {
+        let mut blocks = vec![TextBlock::new(
+            &(self.language.to_string() + ": " + &self.proficiency.to_string()),
+            RenderParams::default().with_offset(indent),
+        )];
+        blocks.append(&mut language_details(self, indent));
+        blocks
+    }
+}
+
+// The certificates and the notes, indented below the language in both layouts.
+fn language_details(lang: &Lang, indent: f64) -> Vec<TextBlock> {
+    let mut blocks = vec![];
+    let params = RenderParams::default().with_offset(indent + NOTES_INDENT);
+    let certificates = lang
+        .certificates
+        .iter()
+        .map(|certificate| certificate.to_string())
+        .collect::<Vec<_>>();
+    if !certificates.is_empty() {
+        let certificates = "Certificates: ".to_string() + &certificates.join(", ");
+        blocks.push(TextBlock::new(&certificates, params.clone()));
+    }
+    push_nonempty(&mut blocks, &lang.notes, params);
+    blocks
+}
+
+// The optional lines of the entries are left out when there is nothing to show.
+fn push_nonempty(blocks: &mut Vec<TextBlock>, text: &str, params: RenderParams) {
+    if !text.is_empty() {
+        blocks.push(TextBlock::new(text, params));
+    }
+}
+
+impl Renderable for Project {
+    fn text_blocks(&self, indent: f64) -> Vec<TextBlock> {
+        let mut blocks = vec![
+            TextBlock::new(
+                &self.name,
+                RenderParams::default()
+                    .with_font_type(FontType::Bold)
+                    .with_offset(indent),
+            ).with_right_aligned(&self.span.to_string(), FontType::Normal),
+        ];
+        let params = RenderParams::default().with_offset(indent);
+        push_nonempty(
+            &mut blocks,
+            &self.role,
+            params.clone().with_font_type(FontType::Italic),
+        );
+        if let Some(ref url) = self.url {
+            blocks.push(TextBlock::new(url.as_str(), params.clone()));
+        }
+        push_nonempty(&mut blocks, &self.technologies.join(", "), params);
+        blocks
+    }
+}
+
+impl Renderable for Publication {
+    fn text_blocks(&self, indent: f64) -> Vec<TextBlock> {
+        let mut blocks = vec![
+            TextBlock::new(
+                &self.title,
+                RenderParams::default()
+                    .with_font_type(FontType::Bold)
+                    .with_offset(indent),
+            ).with_right_aligned(&self.date.to_string(), FontType::Normal),
+            TextBlock::new(
+                &self.venue,
+                RenderParams::default()
+                    .with_font_type(FontType::Italic)
+                    .with_offset(indent),
+            ),
+        ];
+        let params = RenderParams::default().with_offset(indent);
+        if !self.co_authors.is_empty() {
+            let co_authors = "With ".to_string() + &self.co_authors.join(", ");
+            blocks.push(TextBlock::new(&co_authors, params.clone()));
+        }
+        if let Some(ref doi) = self.doi {
+            blocks.push(TextBlock::new(&("DOI: ".to_string() + doi), params.clone()));
+        }
+        if let Some(ref url) = self.url {
+            blocks.push(TextBlock::new(url.as_str(), params));
+        }
+        blocks
+    }
+}
+
+impl Renderable for Certification {
+    fn text_blocks(&self, indent: f64) -> Vec<TextBlock> {
+        let validity = match self.expires {
+            Some(expires) => self.obtained.to_string() + "   -   " + &expires.to_string(),
+            None => self.obtained.to_string(),
+        };
+        let mut blocks = vec![
+            TextBlock::new(
+                &self.name,
+                RenderParams::default()
+                    .with_font_type(FontType::Bold)
+                    .with_offset(indent),
+            ).with_right_aligned(&validity, FontType::Normal),
+            TextBlock::new(
+                &self.issuer,
+                RenderParams::default()
+                    .with_font_type(FontType::Italic)
+                    .with_offset(indent),
+            ),
+        ];
+        if let Some(ref id) = self.credential_id {
+            blocks.push(TextBlock::new(
+                &("Credential ID: ".to_string() + id),
+                RenderParams::default().with_offset(indent),
+            ));
+        }
+        blocks
+    }
+}
+
+impl Renderable for CustomEntry {
+    fn text_blocks(&self, indent: f64) -> Vec<TextBlock> {
+        let mut heading = TextBlock::new(
+            &self.heading,
+            RenderParams::default()
+                .with_font_type(FontType::Bold)
+                .with_offset(indent),
+        );
+        if let Some(ref span) = self.span {
+            heading = heading.with_right_aligned(&span.to_string(), FontType::Normal);
+        }
+        let mut blocks = vec![heading];
+        let params = RenderParams::default().with_offset(indent);
+        push_nonempty(
+            &mut blocks,
+            &self.subheading,
+            params.clone().with_font_type(FontType::Italic),
+        );
+        push_nonempty(&mut blocks, &self.body, params);
+        blocks
+    }
+}
+
+// User supplied TTF/OTF files replacing the bundled faces. Faces which are not set, or which
+// cannot be loaded, fall back to the bundled OpenSans.
+#[derive(Clone, Debug, Default)]
+pub struct FontConfig {
+    pub regular: Option<PathBuf>,
+    pub italic: Option<PathBuf>,
+    pub bold: Option<PathBuf>,
+}
+
+impl FontConfig {
+    // Reads the font paths from the CVMAKER_FONT_REGULAR, CVMAKER_FONT_ITALIC and
+    // CVMAKER_FONT_BOLD environment variables.
+    pub fn from_env() -> FontConfig {
+        let font_path = |var| env::var_os(var).map(PathBuf::from);
+        FontConfig {
+            regular: font_path("CVMAKER_FONT_REGULAR"),
+            italic: font_path("CVMAKER_FONT_ITALIC"),
+            bold: font_path("CVMAKER_FONT_BOLD"),
+        }
+    }
+}
+
+// A font embedded into the document together with its metrics, which are needed to measure
+// the text before it is written.
+struct LoadedFont {
+    reference: IndirectFontRef,
+    face: Font<'static>,
+}
+
+impl LoadedFont {
+    // Loads the font from the user supplied file, if any, and falls back to the bundled one
+    // when there is none or it cannot be used. The reason of the fallback is returned along
+    // with the font.
+    fn load(
+        doc: &PdfDocumentReference,
+        custom: &Option<PathBuf>,
+        bundled: &'static [u8],
+    ) -> CVResult<(LoadedFont, Option<CVError>)> {
+        let mut warning = None;
+        if let Some(path) = custom {
+            let custom_font = fs::read(path)
+                .map_err(|err| font_error(Some(path), err))
+                .and_then(|bytes| Self::from_bytes(doc, Some(path), bytes));
+            match custom_font {
+                Ok(font) => return Ok((font, None)),
+                Err(err) => warning = Some(CVError::FontFallback(Box::new(err))),
+            }
+        }
+        Ok((Self::from_bytes(doc, None, bundled.to_vec())?, warning))
+    }
+
+    fn from_bytes(
+        doc: &PdfDocumentReference,
+        path: Option<&PathBuf>,
+        bytes: Vec<u8>,
+    ) -> CVResult<LoadedFont> {
+        // Parse the font first, the document would not be able to write text in a broken one.
+        let face = Font::from_bytes(bytes.clone()).map_err(|err| font_error(path, err))?;
+        let reference = doc
+            .add_external_font(Cursor::new(bytes))
+            .map_err(|err| font_error(path, pdf_error(err)))?;
+        Ok(LoadedFont { reference, face })
+    }
+
+    // Width of the text when set in this font, based on the advance widths of its glyphs.
+    fn text_width(&self, text: &str, font_size: i64) -> Mm {
+        // Scaling by the unscaled line height makes rusttype return the metrics in font units.
+        let v_metrics = self.face.v_metrics_unscaled();
+        let scale = Scale::uniform(v_metrics.ascent - v_metrics.descent);
+        let units: f32 = text
+            .chars()
+            .map(|c| self.face.glyph(c).scaled(scale).h_metrics().advance_width)
+            .sum();
+        Pt(f64::from(units) * font_size as f64 / f64::from(self.face.units_per_em())).into()
+    }
+}
+
+fn font_error<E: Into<Box<dyn Error>>>(path: Option<&PathBuf>, err: E) -> CVError {
+    CVError::Font {
+        path: path.cloned(),
+        source: err.into(),
+    }
+}
+
+// The errors of printpdf do not implement the Error trait, only their description is kept.
+fn pdf_error(err: printpdf::Error) -> Box<dyn Error> {
+    err.to_string().into()
+}
+
+struct Renderer<'a> {
+    cv: &'a CV,
+    doc: PdfDocumentReference,
+    // Layers of all the pages created so far and the index of the one being written to.
+    // Multi-column templates return to earlier pages when they start a new column.
+    pages: Vec<PdfLayerReference>,
+    page: usize,
+    current: RendererCoordinates,
+    column: Column,
+    style: Style,
+    boundaries: &'a SheetDim,
+    font: LoadedFont,
+    italic_font: LoadedFont,
+    bold_font: LoadedFont,
+    // Problems which have not stopped the rendering, the caller reports them to the user.
+    warnings: Vec<CVError>,
+}
+
+#[derive(Clone)]
+enum FontType {
+    Normal,
+    Italic,
+    Bold,
+}
+
+#[derive(Clone)]
+struct RenderParams {
+    offset: Option<f64>,
+    f_type: FontType,
+}
+
+impl RenderParams {
+    pub fn default() -> Self {
+        RenderParams {
+            offset: None,
+            f_type: FontType::Normal,
+        }
+    }
+
+    pub fn with_offset(mut self, offset: f64) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    pub fn with_font_type(mut self, f_type: FontType) -> Self {
+        self.f_type = f_type;
+        self
+    }
+}
+
+impl<'a> Renderer<'a> {
+    pub fn new(
+        cv: &'a CV,
+        canvas: PdfLayerReference,
+        dim: &'a SheetDim,
+        doc: PdfDocumentReference,
+        fonts: &FontConfig,
+    ) -> CVResult<Self> {
+        let (font, regular_warning) = LoadedFont::load(&doc, &fonts.regular, REGULAR_FONT)?;
+        let (italic_font, italic_warning) = LoadedFont::load(&doc, &fonts.italic, ITALIC_FONT)?;
+        let (bold_font, bold_warning) = LoadedFont::load(&doc, &fonts.bold, BOLD_FONT)?;
+        Ok(Renderer {
+            cv,
+            pages: vec![canvas],
+            page: 0,
+            current: RendererCoordinates::start(dim),
+            column: Column {
+                left: dim.left,
+                width: dim.content_width(),
+            },
+            style: Style::regular(),
+            boundaries: dim,
+            font,
+            italic_font,
+            bold_font,
+            warnings: vec![regular_warning, italic_warning, bold_warning]
+                .into_iter()
+                .flatten()
+                .collect(),
+            doc,
+        })
+    }
+
+    /// This method consumes the object itself. Returns the warnings, see Renderer::warnings.
+    pub fn render<W: Write>(mut self, target: W) -> CVResult<Vec<CVError>> {
+        self.render_content()?;
+        // ISO standard optimized for print production.https://en.wikipedia.org/wiki/PDF/X
+        //self.doc.repair_errors(PdfConformance::X5G_2010_PDF_1_6);
+        self.doc
+            .save(&mut BufWriter::new(target))
+            .map_err(|err| CVError::Rendering {
+                reason: "Could not write the document".to_string(),
+                source: Some(pdf_error(err)),
+            })?;
+        Ok(self.warnings)
+    }
+
+    fn render_content(&mut self) -> RendererResult {
+        layout_for(&self.cv.template).render(self)
+    }
+
+    fn canvas(&self) -> &PdfLayerReference {
+        &self.pages[self.page]
+    }
+
+    // Lays the following text out in the given column, the cursor stays on the same row.
+    fn set_column(&mut self, left: Mm, width: Mm) {
+        self.column = Column { left, width };
+        self.current.col = left;
+    }
+
+    // Moves the cursor to the top of the first page, e.g. to fill in another column.
+    fn move_to_first_page(&mut self) {
+        self.page = 0;
+        self.current.row = RendererCoordinates::start(self.boundaries).row;
+    }
+
+    fn loaded_font(&self, f_type: &FontType) -> &LoadedFont {
+        use self::FontType::*;
+        match f_type {
+            Normal => &self.font,
+            Italic => &self.italic_font,
+            Bold => &self.bold_font,
+        }
+    }
+
+    // Horizontal position at which a text with the given offset starts.
+    fn text_start(&self, offset: Option<f64>) -> Mm {
+        match offset {
+            Some(offset) => self.current.col + Mm(offset),
+            None => self.current.col,
+        }
+    }
+
+    // Horizontal position of the right edge of the current column.
+    fn column_end(&self) -> Mm {
+        self.column.left + self.column.width
+    }
+
+    fn text_width(&self, text: &str, f_type: &FontType) -> Mm {
+        self.loaded_font(f_type).text_width(text, self.style.font_size)
+    }
+
+    // Splits the text into lines which fit between the text start and the column end.
+    fn wrap_text(&self, text: &str, params: &RenderParams) -> Vec<String> {
+        let max_width = self.column_end() - self.text_start(params.offset);
+        self.wrap_text_to_width(text, &params.f_type, max_width)
+    }
+
+    // Explicit newlines are kept, the remaining lines are broken at spaces. Words which are too
+    // long to fit on a line on their own are broken at the last character that still fits.
+    fn wrap_text_to_width(&self, text: &str, f_type: &FontType, max_width: Mm) -> Vec<String> {
+        self.wrap_text_to_widths(text, f_type, max_width, max_width)
+    }
+
+    // As wrap_text_to_width, only the first line of the whole text is given its own width.
+    fn wrap_text_to_widths(
+        &self,
+        text: &str,
+        f_type: &FontType,
+        first_width: Mm,
+        max_width: Mm,
+    ) -> Vec<String> {
+        let font = self.loaded_font(f_type);
+        let font_size = self.style.font_size;
+        let fits = |lines: &Vec<String>, line: &str| {
+            let width = if lines.is_empty() {
+                first_width
+            } else {
+                max_width
+            };
+            font.text_width(line, font_size) <= width
+        };
+        let mut lines = vec![];
+        // The text starts on the next line when the first one has no room for a single
+        // character, e.g. next to a long right-aligned text in a narrow column.
+        if let Some(c) = text.chars().next() {
+            if first_width < max_width && !fits(&lines, &c.to_string()) {
+                lines.push(String::new());
+            }
+        }
+        for paragraph in text.split('\n') {
+            let mut current = String::new();
+            for (i, word) in paragraph.split(' ').enumerate() {
+                let candidate = if i == 0 {
+                    word.to_string()
+                } else {
+                    current.clone() + " " + word
+                };
+                if fits(&lines, &candidate) {
+                    current = candidate;
+                    continue;
+                }
+                if !current.is_empty() {
+                    lines.push(current);
+                }
+                current = String::new();
+                for c in word.chars() {
+                    let mut candidate = current.clone();
+                    candidate.push(c);
+                    if !current.is_empty() && !fits(&lines, &candidate) {
+                        lines.push(current);
+                        candidate = c.to_string();
+                    }
+                    current = candidate;
+                }
+            }
+            lines.push(current);
+        }
+        lines
+    }
+
+    // Height of the text once wrapped.
+    fn text_height(&self, text: &str, params: &RenderParams) -> Mm {
+        self.style.line_height() * self.wrap_text(text, params).len() as f64
+    }
+
+    // Wraps the block text, the first line leaves room for the right-aligned text.
+    fn wrap_block(&self, block: &TextBlock) -> Vec<String> {
+        let max_width = self.column_end() - self.text_start(block.params.offset);
+        let mut first_width = max_width;
+        if let Some((ref text, ref f_type)) = block.right_aligned {
+            first_width -= self.text_width(text, f_type) + RIGHT_ALIGNED_GAP;
+        }
+        self.wrap_text_to_widths(&block.text, &block.params.f_type, first_width, max_width)
+    }
+
+    fn block_height(&self, block: &TextBlock) -> Mm {
+        self.style.line_height() * self.wrap_block(block).len() as f64
+    }
+
+    // Vertical space between the cursor and the bottom margin of the current page.
+    fn remaining_height(&self) -> Mm {
+        self.current.row - self.boundaries.bottom
+    }
+
+    fn is_at_page_start(&self) -> bool {
+        self.current.row == RendererCoordinates::start(self.boundaries).row
+    }
+
+    // Moves the cursor to the top of the current column on the next page. The page (with its
+    // own layer) is created unless another column has done so already.
+    fn new_page(&mut self) {
+        self.page += 1;
+        if self.page == self.pages.len() {
+            let SheetDim { width, height, .. } = *self.boundaries;
+            let (page_idx, layer_idx) = self.doc.add_page(width, height, "main layer");
+            self.pages.push(self.doc.get_page(page_idx).get_layer(layer_idx));
+        }
+        self.current.row = RendererCoordinates::start(self.boundaries).row;
+    }
+
+    // Starts a new page unless there is at least `height` of vertical space left on the current
+    // one. Blocks which would not fit even on an empty page are left on the current page, they
+    // get split line by line instead.
+    fn ensure_space(&mut self, height: Mm) {
+        if self.remaining_height() < height && !self.is_at_page_start() {
+            self.new_page();
+        }
+    }
+
+    // Writes a single line at the given horizontal position on the current row, the cursor
+    // does not move.
+    fn write_line_at(&self, text: &str, x: Mm, f_type: &FontType) {
+        let canvas = self.canvas();
+        let font = &self.loaded_font(f_type).reference;
+        canvas.begin_text_section();
+        canvas.set_font(font, self.style.font_size);
+        canvas.set_line_height(self.style.line_height);
+        canvas.set_text_cursor(x, self.current.row);
+        canvas.write_text(text, font);
+        canvas.end_text_section();
+    }
+
+    // Horizontal position at which the text has to start to end at the right edge of the
+    // current column.
+    fn right_aligned_start(&self, text: &str, f_type: &FontType) -> Mm {
+        self.column_end() - self.text_width(text, f_type)
+    }
+
+    fn render_block(&mut self, block: &TextBlock) {
+        let line_height = self.style.line_height();
+        for (i, line) in self.wrap_block(block).iter().enumerate() {
+            self.ensure_space(line_height);
+            let x = self.text_start(block.params.offset);
+            self.write_line_at(line, x, &block.params.f_type);
+            if let (0, Some((ref text, ref f_type))) = (i, &block.right_aligned) {
+                let x = self.right_aligned_start(text, f_type);
+                self.write_line_at(text, x, f_type);
+            }
+            self.move_cursor_with_offset(&RendererCoordinates {
+                row: line_height,
+                col: Mm(0.0),
+            });
+        }
+    }
+
+    fn render_text_vector(&mut self, data: &[String], params: RenderParams) {
+        for text in data {
+            self.render_block(&TextBlock::new(text, params.clone()));
+        }
+    }
+
+    fn render_text(&mut self, text: &str, render_params: RenderParams) {
+        self.render_text_vector(&[text.to_string()], render_params);
+    }
+
+    /// Implementation note: The y axis is inverted, therefore passing RendererCoordinates { col : Mm(0.0), row : Mm(5.5) }
+    /// will have the intended effect of moving 5.5 mm BELOW, however, the rendering algorithm
+    /// condiders the origin of the document as the left bottom corner, so y the value passed must
+    /// be subtracted from the current cursor.
+    fn move_cursor_with_offset(&mut self, diff: &RendererCoordinates) {
+        self.current.col += diff.col;
+        self.current.row -= diff.row;
+    }
+
+    fn full_name(&self) -> String {
+        self.cv.basic.name.to_string() + " " + &self.cv.basic.surname
+    }
+
+    fn date_of_birth(&self) -> Option<String> {
+        self.cv
+            .basic
+            .dob
+            .map(|dob| "Date of birth: ".to_string() + &dob.to_string())
+    }
+
+    fn contacts(&self) -> Vec<String> {
+        self.cv
+            .basic
+            .contacts
+            .iter()
+            .map(|contact| contact.to_string())
+            .collect()
+    }
+
+    fn custom_fields(&self) -> Vec<String> {
+        self.cv
+            .basic
+            .custom_fields
+            .iter()
+            .map(|field| field.name.to_string() + ": " + &field.value)
+            .collect()
+    }
+
+    fn render_languages(&mut self, indent: f64) -> RendererResult {
+        let cv = self.cv;
+        match cv.language_layout {
+            LanguageLayout::Summary => self.render_data_vector(&cv.languages, "Languages", indent),
+            LanguageLayout::Grid => self.render_language_grid(&cv.languages, indent),
+        }
+    }
+
+    // Every custom section is rendered as the built-in ones, under its own title.
+    fn render_custom_sections(&mut self, indent: f64) -> RendererResult {
+        let cv = self.cv;
+        for section in &cv.custom_sections {
+            self.render_data_vector(&section.entries, &section.title, indent)?;
+        }
+        Ok(())
+    }
+
+    fn render_data_vector<T>(&mut self, data: &[T], label: &str, indent: f64) -> RendererResult
+    where
+        T: Renderable,
+    {
+        if !data.is_empty() {
+            // Do not leave the label orphaned at the bottom of a page.
+            let label_params = RenderParams::default().with_font_type(FontType::Italic);
+            let first_height = data[0].height(self, indent);
+            self.ensure_space(self.text_height(label, &label_params) + first_height);
+            self.render_text(label, label_params);
+            data.iter().for_each(|item| {
+                let height = item.height(self, indent);
+                self.ensure_space(height);
+                item.render_object(self, indent);
+            });
+        }
+        Ok(())
+    }
+}
+
+// Renders the CV into any sink, be it a file or an in-memory buffer. Fonts configured through
+// the environment (see FontConfig::from_env) replace the bundled ones. Returns the warnings,
+// e.g. the custom fonts which could not be used.
+pub fn render_pdf<W: Write>(cv: &CV, target: W) -> CVResult<Vec<CVError>> {
+    render_pdf_with_fonts(cv, target, &FontConfig::from_env())
+}
+
+pub fn render_pdf_with_fonts<W: Write>(
+    cv: &CV,
+    target: W,
+    fonts: &FontConfig,
+) -> CVResult<Vec<CVError>> {
+    let dim = SheetDim::from_page_setup(&cv.page)?;
+    let (doc, page_idx, layer_idx) = PdfDocument::new(
+        format!("CV - {} {}", cv.basic.name, cv.basic.surname),
+        dim.width,
+        dim.height,
+        "main layer".to_string(),
+    );
+    Renderer::new(
+        cv,
+        doc.get_page(page_idx).get_layer(layer_idx),
+        &dim,
+        doc,
+        fonts,
+    )?.render(target)
+}
+
+// Renders the CV into the file on the given path. The file is overwritten if it exists already.
+pub fn render_pdf_to_file<P: AsRef<Path>>(cv: &CV, path: P) -> CVResult<Vec<CVError>> {
+    match File::create(&path) {
+        Ok(file) => render_pdf(cv, file),
+        Err(err) => Err(CVError::io(&path.as_ref().to_string_lossy(), err)),
+    }
+}
+
+// Name of the PDF file the CV is rendered to unless the user says otherwise, e.g.
+// "Peter_Raskolnikov_<id>.pdf". The id is taken from the CV path, CVs which have not been
+// stored yet get no id part.
+pub fn default_file_name(cv: &CV) -> String {
+    let sanitize = |part: &str| {
+        part.chars()
+            .map(|c| if c.is_alphanumeric() { c } else { '_' })
+            .collect::<String>()
+    };
+    let mut parts = vec![sanitize(&cv.basic.name), sanitize(&cv.basic.surname)];
+    let id = cv
+        .path
+        .as_ref()
+        .and_then(|path| Path::new(path).file_stem())
+        .and_then(|stem| stem.to_str());
+    if let Some(id) = id {
+        parts.push(sanitize(id));
+    }
+    parts.retain(|part| !part.is_empty());
+    parts.join("_") + ".pdf"
+}
+
+//TODO write a bit more tests.
+#[cfg(test)]
+pub mod test {
+    use super::*;
+    use base::test::{
+        assessed_lang_factory, basic_cv_factory, certification_factory, custom_section_factory,
+        experience_factory, project_factory, publication_factory,
+    };
+    use base::{Address, Contact, CustomField, Margins, PaperSize, Template};
+    use isocountry::CountryCode;
+    use std::str::FromStr;
+
+    // Runs the closure with a renderer drawing the given CV onto a fresh A4 document.
+    pub(super) fn with_renderer<F, R>(cv: &CV, f: F) -> R
+    where
+        F: FnOnce(&mut Renderer) -> R,
+    {
+        let dim = SheetDim::from_page_setup(&cv.page).unwrap();
+        let (doc, page_idx, layer_idx) =
+            PdfDocument::new("test", dim.width, dim.height, "main layer");
+        let mut renderer = Renderer::new(
+            cv,
+            doc.get_page(page_idx).get_layer(layer_idx),
+            &dim,
+            doc,
+            &FontConfig::default(),
+        ).unwrap();
+        f(&mut renderer)
+    }
+
+    fn count_pages(cv: &CV) -> usize {
+        with_renderer(cv, |renderer| {
+            renderer.render_content().unwrap();
+            renderer.pages.len()
+        })
+    }
+
+    fn long_description() -> String {
+        "Designed, implemented and maintained a distributed build system used by \
+         hundreds of engineers, cutting the average build time in half."
+            .to_string()
+    }
+
+    #[test]
+    fn render_into_memory() {
+        let mut bytes: Vec<u8> = vec![];
+        render_pdf(&basic_cv_factory(), &mut bytes).unwrap();
+        assert!(bytes.starts_with(b"%PDF"));
+    }
+
+    #[test]
+    fn missing_custom_font_falls_back_to_bundled() {
+        let fonts = FontConfig {
+            regular: Some(PathBuf::from("/nonexistent/font.ttf")),
+            ..Default::default()
+        };
+        let mut bytes: Vec<u8> = vec![];
+        let warnings = render_pdf_with_fonts(&basic_cv_factory(), &mut bytes, &fonts).unwrap();
+        assert!(bytes.starts_with(b"%PDF"));
+        match warnings.as_slice() {
+            [CVError::FontFallback(err)] => match **err {
+                CVError::Font { ref path, .. } => {
+                    assert_eq!(&Some(PathBuf::from("/nonexistent/font.ttf")), path)
+                }
+                ref err => panic!("Expected a font error, got {:?}", err),
+            },
+            warnings => panic!("Expected a single fallback, got {:?}", warnings),
+        }
+    }
+
+    #[test]
+    fn custom_font_used() {
+        let (doc, _, _) = PdfDocument::new("test", Mm(210.0), Mm(297.0), "main layer");
+        let (bundled, _) = LoadedFont::load(&doc, &None, REGULAR_FONT).unwrap();
+        let (custom, warning) = LoadedFont::load(
+            &doc,
+            &Some(PathBuf::from("src/resources/fonts/OpenSans-ExtraBold.ttf")),
+            REGULAR_FONT,
+        ).unwrap();
+        assert!(warning.is_none());
+        let font_size = Style::regular().font_size;
+        assert!(custom.text_width("CV", font_size) > bundled.text_width("CV", font_size));
+    }
+
+    #[test]
+    fn default_file_name_without_id() {
+        assert_eq!(
+            "Peter_Raskolnikov.pdf",
+            default_file_name(&basic_cv_factory())
+        );
+    }
+
+    #[test]
+    fn default_file_name_with_id() {
+        let mut cv = basic_cv_factory();
+        cv.basic.surname = "Raskolnikov Jr.".to_string();
+        cv.path = Some("/cvs/0123abcd.json".to_string());
+        assert_eq!(
+            "Peter_Raskolnikov_Jr__0123abcd.pdf",
+            default_file_name(&cv)
+        );
+    }
+
+    #[test]
+    fn named_paper_size_used() {
+        let mut cv = basic_cv_factory();
+        cv.page.paper = PaperSize::Letter;
+        with_renderer(&cv, |renderer| {
+            assert_eq!(Mm(215.9), renderer.boundaries.width);
+            assert_eq!(Mm(279.4), renderer.boundaries.height);
+        });
+    }
+
+    #[test]
+    fn margins_used_for_positioning() {
+        let mut cv = basic_cv_factory();
+        cv.page.margins = Margins {
+            top: 20.0,
+            bottom: 25.0,
+            left: 30.0,
+            right: 10.0,
+        };
+        with_renderer(&cv, |renderer| {
+            assert_eq!(Mm(30.0), renderer.text_start(None));
+            assert_eq!(Mm(297.0 - 20.0), renderer.current.row);
+            assert_eq!(Mm(297.0 - 20.0 - 25.0), renderer.remaining_height());
+            assert_eq!(Mm(210.0 - 30.0 - 10.0), renderer.boundaries.content_width());
+        });
+    }
+
+    #[test]
+    fn margins_wider_than_paper_rejected() {
+        let mut cv = basic_cv_factory();
+        cv.page.paper = PaperSize::Custom {
+            width: 50.0,
+            height: 50.0,
+        };
+        cv.page.margins.left = 40.0;
+        let mut bytes: Vec<u8> = vec![];
+        assert!(render_pdf(&cv, &mut bytes).is_err());
+    }
+
+    #[test]
+    fn non_finite_page_setup_rejected() {
+        for value in &["NaN", "inf"] {
+            let value = f64::from_str(value).unwrap();
+            let setup = PageSetup {
+                paper: PaperSize::Custom {
+                    width: value,
+                    height: 200.0,
+                },
+                ..Default::default()
+            };
+            assert!(SheetDim::from_page_setup(&setup).is_err());
+            let mut setup = PageSetup::default();
+            setup.margins.bottom = value;
+            assert!(SheetDim::from_page_setup(&setup).is_err());
+        }
+    }
+
+    #[test]
+    fn short_cv_single_page() {
+        assert_eq!(1, count_pages(&basic_cv_factory()));
+    }
+
+    #[test]
+    fn long_cv_spans_multiple_pages() {
+        let mut cv = basic_cv_factory();
+        cv.experience = (0..30).map(|_| experience_factory()).collect();
+        assert!(count_pages(&cv) > 1);
+    }
+
+    fn long_cv(template: Template) -> CV {
+        let mut cv = basic_cv_factory();
+        cv.experience = (0..30).map(|_| experience_factory()).collect();
+        cv.template = template;
+        cv
+    }
+
+    #[test]
+    fn every_template_renders() {
+        for template in Template::iterator() {
+            let mut cv = basic_cv_factory();
+            cv.template = template.clone();
+            assert_eq!(1, count_pages(&cv));
+        }
+    }
+
+    #[test]
+    fn every_template_renders_all_sections() {
+        for template in Template::iterator() {
+            let mut cv = basic_cv_factory();
+            cv.projects = vec![project_factory()];
+            cv.publications = vec![publication_factory()];
+            cv.certifications = vec![certification_factory()];
+            cv.custom_sections = vec![custom_section_factory()];
+            cv.basic.custom_fields = vec![CustomField {
+                name: "Driving licence".to_string(),
+                value: "B".to_string(),
+            }];
+            cv.template = template.clone();
+            let mut bytes: Vec<u8> = vec![];
+            render_pdf(&cv, &mut bytes).unwrap();
+            assert!(bytes.starts_with(b"%PDF"));
+        }
+    }
+
+    #[test]
+    fn optional_lines_left_out() {
+        let mut project = project_factory();
+        assert_eq!(4, project.text_blocks(0.0).len());
+        project.url = None;
+        project.technologies = vec![];
+        assert_eq!(2, project.text_blocks(0.0).len());
+        let mut publication = publication_factory();
+        assert_eq!(4, publication.text_blocks(0.0).len());
+        publication.co_authors = vec![];
+        publication.doi = None;
+        assert_eq!(2, publication.text_blocks(0.0).len());
+        let mut certification = certification_factory();
+        assert_eq!(3, certification.text_blocks(0.0).len());
+        certification.credential_id = None;
+        certification.expires = None;
+        let blocks = certification.text_blocks(0.0);
+        assert_eq!(2, blocks.len());
+        let (ref validity, _) = blocks[0].right_aligned.clone().unwrap();
+        assert_eq!("2001-02-01", validity);
+        let section = custom_section_factory();
+        assert_eq!(3, section.entries[0].text_blocks(0.0).len());
+        let blocks = section.entries[1].text_blocks(0.0);
+        assert_eq!(1, blocks.len());
+        assert!(blocks[0].right_aligned.is_none());
+    }
+
+    #[test]
+    fn language_details_left_out_when_empty() {
+        let mut lang = assessed_lang_factory();
+        let blocks = lang.text_blocks(0.0);
+        assert_eq!(2, blocks.len());
+        assert_eq!(
+            "Certificates: Goethe-Zertifikat B2, TestDaF (TDN 4)",
+            blocks[1].text
+        );
+        lang.certificates = vec![];
+        lang.notes = "Lived in Berlin".to_string();
+        assert_eq!("Lived in Berlin", lang.text_blocks(0.0)[1].text);
+    }
+
+    #[test]
+    fn every_template_renders_language_grid() {
+        for template in Template::iterator() {
+            let mut cv = basic_cv_factory();
+            let mut unassessed = assessed_lang_factory();
+            unassessed.skills = None;
+            cv.languages = vec![assessed_lang_factory(), unassessed];
+            cv.language_layout = LanguageLayout::Grid;
+            cv.template = template.clone();
+            let mut bytes: Vec<u8> = vec![];
+            render_pdf(&cv, &mut bytes).unwrap();
+            assert!(bytes.starts_with(b"%PDF"));
+        }
+    }
+
+    #[test]
+    fn address_formatted_for_country() {
+        let mut cv = basic_cv_factory();
+        let address = Address::new(
+            vec!["221B Baker Street".to_string()],
+            "London",
+            None,
+            Some("NW1 6XE".to_string()),
+            CountryCode::for_alpha2("GB").unwrap(),
+        ).unwrap();
+        cv.basic.contacts.push(Contact::Address(address));
+        let contacts = with_renderer(&cv, |renderer| renderer.contacts());
+        assert!(contacts[1].starts_with("Address : 221B Baker Street, London, NW1 6XE, United"));
+        let mut bytes: Vec<u8> = vec![];
+        render_pdf(&cv, &mut bytes).unwrap();
+    }
+
+    #[test]
+    fn compact_template_fits_more_on_a_page() {
+        let compact = count_pages(&long_cv(Template::Compact));
+        assert!(compact < count_pages(&long_cv(Template::Classic)));
+    }
+
+    #[test]
+    fn two_column_template_shares_pages_between_columns() {
+        with_renderer(&long_cv(Template::TwoColumn), |renderer| {
+            renderer.render_content().unwrap();
+            let pages = renderer.pages.len();
+            assert!(pages > 1);
+            // The main column continued on the pages created for it, the sidebar is short.
+            assert_eq!(pages - 1, renderer.page);
+            assert!(renderer.column.left > renderer.boundaries.left);
+        });
+    }
+
+    #[test]
+    fn entry_not_split_across_pages() {
+        with_renderer(&basic_cv_factory(), |renderer| {
+            let experience = experience_factory();
+            // Leave room for a single line only.
+            renderer.current.row = renderer.boundaries.bottom + renderer.style.line_height();
+            let height = experience.height(renderer, 0.0);
+            renderer.ensure_space(height);
+            assert_eq!(2, renderer.pages.len());
+            assert!(renderer.is_at_page_start());
+        });
+    }
+
+    #[test]
+    fn span_flush_with_right_margin() {
+        with_renderer(&basic_cv_factory(), |renderer| {
+            let experience = experience_factory();
+            let span = experience.span.to_string();
+            let start = renderer.right_aligned_start(&span, &FontType::Normal);
+            let end = start + renderer.text_width(&span, &FontType::Normal);
+            assert_eq!(renderer.boundaries.width - renderer.boundaries.right, end);
+            // The start depends only on the span, not on the employer.
+            let mut other = experience_factory();
+            other.employer = "A much longer employer name, ltd.".to_string();
+            assert_eq!(
+                start,
+                renderer.right_aligned_start(&other.span.to_string(), &FontType::Normal)
+            );
+        });
+    }
+
+    #[test]
+    fn text_next_to_right_aligned_wrapped_before_it() {
+        with_renderer(&basic_cv_factory(), |renderer| {
+            let span = "2000-01-01   -   2001-01-01";
+            let block = TextBlock::new(&long_description(), RenderParams::default())
+                .with_right_aligned(span, FontType::Normal);
+            let span_start = renderer.right_aligned_start(span, &FontType::Normal);
+            let lines = renderer.wrap_block(&block);
+            assert!(lines.len() >= renderer.wrap_text(&block.text, &block.params).len());
+            let first_end =
+                renderer.text_start(None) + renderer.text_width(&lines[0], &FontType::Normal);
+            assert!(first_end + RIGHT_ALIGNED_GAP <= span_start);
+        });
+    }
+
+    #[test]
+    fn text_moved_below_right_aligned_wider_than_column() {
+        with_renderer(&basic_cv_factory(), |renderer| {
+            let span = "January 2015   -   present";
+            let span_width = renderer.text_width(span, &FontType::Normal);
+            let left = renderer.column.left;
+            renderer.set_column(left, span_width * 0.75);
+            let block = TextBlock::new("Dostoevsky & sons", RenderParams::default())
+                .with_right_aligned(span, FontType::Normal);
+            let lines = renderer.wrap_block(&block);
+            assert_eq!("", lines[0]);
+            assert_eq!("Dostoevsky & sons", lines[1..].join(" "));
+        });
+    }
+
+    #[test]
+    fn only_first_line_narrowed() {
+        with_renderer(&basic_cv_factory(), |renderer| {
+            let span = "1 January 2000   -   1 January 2001, and then some more";
+            let block = TextBlock::new(&long_description(), RenderParams::default())
+                .with_right_aligned(span, FontType::Normal);
+            let lines = renderer.wrap_block(&block);
+            let max_width = renderer.column_end() - renderer.text_start(None);
+            let first_width =
+                max_width - renderer.text_width(span, &FontType::Normal) - RIGHT_ALIGNED_GAP;
+            assert!(renderer.text_width(&lines[0], &FontType::Normal) <= first_width);
+            let full = renderer.wrap_text(&lines[1..].join(" "), &block.params);
+            assert_eq!(full, lines[1..].to_vec());
+            assert!(renderer.text_width(&lines[1], &FontType::Normal) > first_width);
+        });
+    }
+
+    #[test]
+    fn short_text_not_wrapped() {
+        with_renderer(&basic_cv_factory(), |renderer| {
+            assert_eq!(
+                vec!["I loved it!".to_string()],
+                renderer.wrap_text("I loved it!", &RenderParams::default())
+            );
+        });
+    }
+
+    #[test]
+    fn long_text_wrapped_to_available_width() {
+        with_renderer(&basic_cv_factory(), |renderer| {
+            let Mm(width) = renderer.boundaries.content_width();
+            let params = RenderParams::default().with_offset(width * 0.25);
+            let lines = renderer.wrap_text(&long_description(), &params);
+            assert!(lines.len() > 1);
+            let max_width = renderer.boundaries.width
+                - renderer.boundaries.right
+                - renderer.text_start(params.offset);
+            for line in &lines {
+                assert!(renderer.font.text_width(line, renderer.style.font_size) <= max_width);
+            }
+            assert_eq!(long_description(), lines.join(" "));
+        });
+    }
+
+    #[test]
+    fn explicit_newlines_respected() {
+        with_renderer(&basic_cv_factory(), |renderer| {
+            assert_eq!(
+                vec!["First line".to_string(), String::new(), "Third line".to_string()],
+                renderer.wrap_text("First line\n\nThird line", &RenderParams::default())
+            );
+        });
+    }
+
+    #[test]
+    fn overlong_word_broken() {
+        with_renderer(&basic_cv_factory(), |renderer| {
+            let word = "x".repeat(200);
+            let lines = renderer.wrap_text(&word, &RenderParams::default());
+            assert!(lines.len() > 1);
+            assert_eq!(word, lines.concat());
+        });
+    }
+}
@@ -0,0 +1,104 @@
+// Responsibility: place the CV sections on the page. Every template drives the same Renderer,
+// templates only decide which section goes to which column and which style is used.
+use super::{FontType, RenderParams, Renderer, RendererResult, Style};
+use base::Template;
+use printpdf::Mm;
+
+// Fraction of the content width occupied by the sidebar of the two-column template.
+const SIDEBAR_RATIO: f64 = 0.3;
+// Blank space between the sidebar and the main column.
+const COLUMN_GAP: Mm = Mm(5.0);
+
+pub trait Layout {
+    fn render(&self, renderer: &mut Renderer) -> RendererResult;
+}
+
+pub fn layout_for(template: &Template) -> Box<dyn Layout> {
+    match template {
+        Template::Classic => Box::new(Classic),
+        Template::TwoColumn => Box::new(TwoColumn),
+        Template::Compact => Box::new(Compact),
+    }
+}
+
+// Single column, the entries are indented by a quarter of the page.
+struct Classic;
+
+impl Layout for Classic {
+    fn render(&self, renderer: &mut Renderer) -> RendererResult {
+        let cv = renderer.cv;
+        let mut basic_vec = vec![renderer.full_name()];
+        basic_vec.extend(renderer.date_of_birth());
+        basic_vec.append(&mut renderer.contacts());
+        basic_vec.append(&mut renderer.custom_fields());
+        renderer.render_text_vector(&basic_vec, RenderParams::default());
+        let Mm(width) = renderer.column.width;
+        let indent = width * 0.25;
+        renderer.render_data_vector(&cv.experience, "Experience", indent)?;
+        renderer.render_data_vector(&cv.education, "Education", indent)?;
+        renderer.render_data_vector(&cv.projects, "Projects", indent)?;
+        renderer.render_data_vector(&cv.publications, "Publications", indent)?;
+        renderer.render_data_vector(&cv.certifications, "Certifications", indent)?;
+        renderer.render_custom_sections(indent)?;
+        renderer.render_languages(indent)
+    }
+}
+
+// Contacts and languages go to a narrow sidebar on the left, the rest to the main column.
+struct TwoColumn;
+
+impl Layout for TwoColumn {
+    fn render(&self, renderer: &mut Renderer) -> RendererResult {
+        let cv = renderer.cv;
+        let Mm(content_width) = renderer.boundaries.content_width();
+        let left = renderer.boundaries.left;
+        let sidebar_width = Mm(content_width * SIDEBAR_RATIO);
+
+        renderer.set_column(left, sidebar_width);
+        renderer.render_text("Contacts", RenderParams::default().with_font_type(FontType::Italic));
+        let mut contacts = renderer.contacts();
+        contacts.extend(renderer.date_of_birth());
+        contacts.append(&mut renderer.custom_fields());
+        renderer.render_text_vector(&contacts, RenderParams::default());
+        renderer.render_languages(0.0)?;
+
+        renderer.move_to_first_page();
+        renderer.set_column(
+            left + sidebar_width + COLUMN_GAP,
+            Mm(content_width) - sidebar_width - COLUMN_GAP,
+        );
+        let name = renderer.full_name();
+        renderer.render_text(&name, RenderParams::default().with_font_type(FontType::Bold));
+        renderer.render_data_vector(&cv.experience, "Experience", 0.0)?;
+        renderer.render_data_vector(&cv.education, "Education", 0.0)?;
+        renderer.render_data_vector(&cv.projects, "Projects", 0.0)?;
+        renderer.render_data_vector(&cv.publications, "Publications", 0.0)?;
+        renderer.render_data_vector(&cv.certifications, "Certifications", 0.0)?;
+        renderer.render_custom_sections(0.0)
+    }
+}
+
+// Smaller type, the personal details are squeezed into a single line and entries are not
+// indented, which fits the most content on a page.
+struct Compact;
+
+impl Layout for Compact {
+    fn render(&self, renderer: &mut Renderer) -> RendererResult {
+        let cv = renderer.cv;
+        renderer.style = Style::compact();
+        let name = renderer.full_name();
+        renderer.render_text(&name, RenderParams::default().with_font_type(FontType::Bold));
+        let mut details = vec![];
+        details.extend(renderer.date_of_birth());
+        details.append(&mut renderer.contacts());
+        details.append(&mut renderer.custom_fields());
+        renderer.render_text(&details.join(" | "), RenderParams::default());
+        renderer.render_data_vector(&cv.experience, "Experience", 0.0)?;
+        renderer.render_data_vector(&cv.education, "Education", 0.0)?;
+        renderer.render_data_vector(&cv.projects, "Projects", 0.0)?;
+        renderer.render_data_vector(&cv.publications, "Publications", 0.0)?;
+        renderer.render_data_vector(&cv.certifications, "Certifications", 0.0)?;
+        renderer.render_custom_sections(0.0)?;
+        renderer.render_languages(0.0)
+    }
+}
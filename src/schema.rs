@@ -0,0 +1,214 @@
+// Responsibility: the versions of the JSON format the CVs and the profile are stored in. Every
+// document carries the version of the format it has been written in. The older documents are
+// upgraded step by step when they are loaded, so that the structs in base only have to read the
+// current format.
+use base::{Profile, CV};
+use error::{CVError, CVResult};
+use serde::de::DeserializeOwned;
+use serde_json::{self, Map, Value};
+
+// The version the CVs are written in. Whenever the format changes in a way the older documents
+// cannot be read with, bump the version and add the migration from the previous one below.
+pub const CURRENT_VERSION: u32 = 3;
+
+const VERSION_FIELD: &str = "version";
+
+// The migration on the index n upgrades a document of the version n to the version n + 1. The
+// documents written before the versioning was introduced are of the version 0. The migrations
+// are run on both the CVs and the profile, they may only rely on the parts the two share.
+const MIGRATIONS: &[fn(&mut Map<String, Value>)] =
+    &[from_unversioned, to_address_lines, to_language_codes];
+
+// The unversioned documents differ from the first version only in the fields added over time
+// (the page setup, the template, the label and the profile link), all of which have defaults.
+fn from_unversioned(_: &mut Map<String, Value>) {}
+
+// The second version replaced the street, the building number and the numeric postal code of
+// the addresses with the free-form lines, the city, the region and a textual postcode.
+fn to_address_lines(document: &mut Map<String, Value>) {
+    let contacts = document
+        .get_mut("basic")
+        .and_then(|basic| basic.get_mut("contacts"))
+        .and_then(Value::as_array_mut);
+    for contact in contacts.into_iter().flat_map(|contacts| contacts.iter_mut()) {
+        if let Some(address) = contact.get_mut("Address").and_then(Value::as_object_mut) {
+            upgrade_address(address);
+        }
+    }
+}
+
+// The old addresses had no city, the street and the number end up on the first line. The
+// leading zeros the numeric postal codes have lost cannot be brought back.
+fn upgrade_address(address: &mut Map<String, Value>) {
+    let street = address.remove("street");
+    let number = address.remove("street_subunit");
+    let line = vec![street, number]
+        .into_iter()
+        .flat_map(|part| match part {
+            Some(Value::String(text)) => Some(text),
+            Some(Value::Number(number)) => Some(number.to_string()),
+            _ => None,
+        }).collect::<Vec<_>>()
+        .join(" ");
+    address.insert("lines".to_string(), vec![line].into());
+    address.insert("city".to_string(), "".into());
+    if let Some(Value::Number(code)) = address.remove("postal_code") {
+        address.insert("postal_code".to_string(), code.to_string().into());
+    }
+}
+
+// The third version replaced the few languages the application used to know by the ISO 639-3
+// codes. The "Other" language becomes "mis", the code reserved for the uncoded languages.
+fn to_language_codes(document: &mut Map<String, Value>) {
+    let languages = document.get_mut("languages").and_then(Value::as_array_mut);
+    for lang in languages.into_iter().flat_map(|languages| languages.iter_mut()) {
+        if let Some(language) = lang.get_mut("language") {
+            let code = language.as_str().and_then(language_code);
+            if let Some(code) = code {
+                *language = code.into();
+            }
+        }
+    }
+}
+
+fn language_code(name: &str) -> Option<&'static str> {
+    let code = match name {
+        "Czech" => "ces",
+        "Slovak" => "slk",
+        "English" => "eng",
+        "Russian" => "rus",
+        "German" => "deu",
+        "Spanish" => "spa",
+        "Chinese" => "zho",
+        "Dutch" => "nld",
+        "French" => "fra",
+        "Polish" => "pol",
+        "Italian" => "ita",
+        "Arabic" => "ara",
+        "Portugese" => "por",
+        "Korean" => "kor",
+        "Other" => "mis",
+        _ => return None,
+    };
+    Some(code)
+}
+
+// Reads the CV from the JSON of any of the supported versions. The path is used in the errors.
+pub fn cv_from_json(json: &str, path: Option<&str>) -> CVResult<CV> {
+    from_json(json, path)
+}
+
+// Reads the profile shared by the CVs, see cv_from_json.
+pub fn profile_from_json(json: &str, path: Option<&str>) -> CVResult<Profile> {
+    from_json(json, path)
+}
+
+fn from_json<T: DeserializeOwned>(json: &str, path: Option<&str>) -> CVResult<T> {
+    let serialization = |source| CVError::Serialization {
+        path: path.map(str::to_string),
+        source,
+    };
+    let mut document: Value = serde_json::from_str(json).map_err(serialization)?;
+    upgrade(&mut document).map_err(|version| CVError::UnsupportedVersion {
+        path: path.map(str::to_string),
+        version,
+    })?;
+    serde_json::from_value(document).map_err(serialization)
+}
+
+// Brings the document to the current version. Fails with the version of the document when it
+// is newer than the current one. The documents which are not even objects are left for the
+// deserialization to reject.
+fn upgrade(document: &mut Value) -> Result<(), u64> {
+    let fields = match document.as_object_mut() {
+        Some(fields) => fields,
+        None => return Ok(()),
+    };
+    let version = match fields.get(VERSION_FIELD) {
+        None => 0,
+        Some(version) => match version.as_u64() {
+            Some(version) => version,
+            None => return Ok(()),
+        },
+    };
+    if version > u64::from(CURRENT_VERSION) {
+        return Err(version);
+    }
+    for migration in &MIGRATIONS[version as usize..] {
+        migration(fields);
+    }
+    fields.insert(VERSION_FIELD.to_string(), CURRENT_VERSION.into());
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use base::test::basic_cv_factory;
+    use base::Contact;
+
+    #[test]
+    fn migration_for_every_version() {
+        assert_eq!(CURRENT_VERSION as usize, MIGRATIONS.len());
+    }
+
+    #[test]
+    fn current_version_written() {
+        let json = serde_json::to_value(basic_cv_factory()).unwrap();
+        assert_eq!(Some(u64::from(CURRENT_VERSION)), json[VERSION_FIELD].as_u64());
+    }
+
+    #[test]
+    fn newer_version_rejected() {
+        let mut json = serde_json::to_value(basic_cv_factory()).unwrap();
+        json[VERSION_FIELD] = (CURRENT_VERSION + 1).into();
+        match cv_from_json(&json.to_string(), Some("/cv.json")) {
+            Err(CVError::UnsupportedVersion { path, version }) => {
+                assert_eq!(Some("/cv.json".to_string()), path);
+                assert_eq!(u64::from(CURRENT_VERSION + 1), version);
+            }
+            res => panic!("Expected the version to be rejected, got {:?}", res),
+        }
+    }
+
+    #[test]
+    fn addresses_get_lines_and_textual_postal_code() {
+        let json = r#"{"version":1,"basic":{"name":"Jane","surname":"Doe","dob":null,
+            "contacts":[{"Address":{"street":"Main","street_subunit":1,"postal_code":60200,
+            "country":"CZ"}}]}}"#;
+        let profile = profile_from_json(json, None).unwrap();
+        assert_eq!(CURRENT_VERSION, profile.version);
+        match profile.basic.contacts[0] {
+            Contact::Address(ref address) => {
+                assert_eq!(vec!["Main 1".to_string()], address.lines);
+                assert_eq!(Some("60200".to_string()), address.postal_code);
+            }
+            ref contact => panic!("Expected an address, got {:?}", contact),
+        }
+    }
+
+    #[test]
+    fn languages_get_codes() {
+        let json = r#"{"version":2,"basic":{"name":"Jane","surname":"Doe","dob":null,
+            "contacts":[{"Email":{"address":"jane@doe.com"}}]},"languages":[
+            {"language":"Portugese","proficiency":"B1","notes":""},
+            {"language":"Other","proficiency":"A1","notes":"Klingon"}]}"#;
+        let profile = profile_from_json(json, None).unwrap();
+        let codes = profile
+            .languages
+            .iter()
+            .map(|lang| lang.language.code())
+            .collect::<Vec<_>>();
+        assert_eq!(vec!["por", "mis"], codes);
+    }
+
+    #[test]
+    fn malformed_documents_rejected() {
+        for json in &["[]", r#"{"version":"one"}"#, "{"] {
+            match cv_from_json(json, None) {
+                Err(CVError::Serialization { .. }) => (),
+                res => panic!("Expected {} to be rejected, got {:?}", json, res),
+            }
+        }
+    }
+}